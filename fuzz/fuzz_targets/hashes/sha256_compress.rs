@@ -0,0 +1,35 @@
+use bitcoin::hashes::sha256;
+use honggfuzz::fuzz;
+
+fn do_test(data: &[u8]) {
+    if data.len() < 32 + 64 {
+        return;
+    }
+    let (midstate, block) = data.split_at(32);
+    let midstate = sha256::Midstate::new(midstate.try_into().unwrap(), 0);
+    let block: &[u8; 64] = block[..64].try_into().unwrap();
+
+    let (bytes, _) = midstate.to_parts();
+    let mut state = [0u32; 8];
+    for (word, chunk) in state.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    let mut software = state;
+
+    // Uses SHA-NI if the CPU supports it.
+    sha256::compress(&mut state, block);
+    sha256::compress_software(&mut software, block);
+    assert_eq!(state, software);
+
+    let (bytes, bytes_hashed) = sha256::Midstate::process_one_block(midstate, block).to_parts();
+    assert_eq!(bytes_hashed, 64);
+    for (word, chunk) in software.iter().zip(bytes.chunks_exact(4)) {
+        assert_eq!(word.to_be_bytes(), chunk);
+    }
+}
+
+fn main() {
+    loop {
+        fuzz!(|d| { do_test(d) });
+    }
+}
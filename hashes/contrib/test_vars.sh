@@ -5,10 +5,10 @@
 # shellcheck disable=SC2034
 
 # Test all these features with "std" enabled.
-FEATURES_WITH_STD="serde small-hash"
+FEATURES_WITH_STD="serde small-hash tracing backend-sha2 tokio derive subtle self-test bytes mmap"
 
 # Test all these features without "std" enabled.
-FEATURES_WITHOUT_STD="alloc serde small-hash"
+FEATURES_WITHOUT_STD="alloc serde small-hash tracing backend-sha2 derive subtle self-test bytes"
 
 # Run these examples.
 EXAMPLES=""
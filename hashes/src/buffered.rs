@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Hash engine wrapper that coalesces small inputs.
+//!
+//! Useful in `no_std` environments without an allocator that receive data in many tiny slices,
+//! each call to the wrapped engine's `input` only happens once a full buffer is available.
+
+use crate::HashEngine;
+
+/// A [`HashEngine`] wrapper that buffers up to `N` bytes on the stack before inputting them.
+///
+/// `N` should be a multiple of the wrapped engine's [`HashEngine::BLOCK_SIZE`]. Inputs larger than
+/// the buffer are passed straight through. Finalizing this engine produces exactly the same hash
+/// as finalizing the wrapped engine would.
+#[derive(Debug, Clone)]
+pub struct BufferedEngine<E: HashEngine, const N: usize> {
+    engine: E,
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<E: HashEngine, const N: usize> BufferedEngine<E, N> {
+    /// Constructs a new buffered engine wrapping `engine`.
+    pub fn new(engine: E) -> Self { Self { engine, buffer: [0; N], len: 0 } }
+
+    /// Inputs any buffered bytes into the wrapped engine and returns it.
+    pub fn into_inner(mut self) -> E {
+        self.flush();
+        self.engine
+    }
+
+    fn flush(&mut self) {
+        self.engine.input(&self.buffer[..self.len]);
+        self.len = 0;
+    }
+}
+
+impl<E: HashEngine + Default, const N: usize> Default for BufferedEngine<E, N> {
+    fn default() -> Self { Self::new(E::default()) }
+}
+
+impl<E: HashEngine, const N: usize> HashEngine for BufferedEngine<E, N> {
+    type Hash = E::Hash;
+    type Bytes = E::Bytes;
+    const BLOCK_SIZE: usize = E::BLOCK_SIZE;
+
+    fn input(&mut self, mut data: &[u8]) {
+        if data.len() < N - self.len {
+            self.buffer[self.len..self.len + data.len()].copy_from_slice(data);
+            self.len += data.len();
+            return;
+        }
+
+        if self.len > 0 {
+            let (head, tail) = data.split_at(N - self.len);
+            self.buffer[self.len..].copy_from_slice(head);
+            self.len = N;
+            self.flush();
+            data = tail;
+        }
+
+        let direct = data.len() - data.len() % N.max(1);
+        self.engine.input(&data[..direct]);
+        let rest = &data[direct..];
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+    }
+    fn n_bytes_hashed(&self) -> u64 { self.engine.n_bytes_hashed() + self.len as u64 }
+    fn finalize(self) -> Self::Hash { self.into_inner().finalize() }
+}
+
+// `impl_write` does not support const generics.
+#[cfg(feature = "std")]
+impl<E: HashEngine, const N: usize> std::io::Write for BufferedEngine<E, N> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+#[cfg(feature = "tokio")]
+impl<E: HashEngine + Unpin, const N: usize> tokio::io::AsyncWrite for BufferedEngine<E, N> {
+    #[inline]
+    fn poll_write(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+        buf: &[u8],
+    ) -> core::task::Poll<std::io::Result<usize>> {
+        self.get_mut().input(buf);
+        core::task::Poll::Ready(Ok(buf.len()))
+    }
+
+    #[inline]
+    fn poll_flush(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_shutdown(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256;
+
+    #[test]
+    fn buffered_engine() {
+        let data: [u8; 300] = core::array::from_fn(|i| i as u8);
+
+        for step in [1, 3, 63, 64, 65, 200] {
+            let mut engine = BufferedEngine::<sha256::HashEngine, 64>::default();
+            for chunk in data.chunks(step) {
+                engine.input(chunk);
+                assert!(engine.engine.n_bytes_hashed() % 64 == 0);
+            }
+            assert_eq!(engine.n_bytes_hashed(), 300);
+            assert_eq!(engine.finalize(), sha256::Hash::hash(&data));
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Fluent interface for hashing structured data.
+//!
+//! ```
+//! use bitcoin_hashes::sha256;
+//!
+//! let _hash = sha256::Hash::builder()
+//!     .chain(b"some data")
+//!     .chain_u32_le(1)
+//!     .chain_varint(3)
+//!     .chain([0xab; 3])
+//!     .finalize();
+//! ```
+
+use crate::HashEngine;
+
+/// Wraps a [`HashEngine`] providing chainable input functions.
+///
+/// The integer helpers encode values the same way Bitcoin consensus serialization does, which
+/// avoids having to manually pick the correct `to_le_bytes`/`to_be_bytes` at every call site.
+#[derive(Debug, Clone, Default)]
+pub struct Builder<E: HashEngine>(E);
+
+impl<E: HashEngine> Builder<E> {
+    /// Constructs a new builder that inputs data into `engine`.
+    pub fn new(engine: E) -> Self { Self(engine) }
+
+    /// Inputs `data` into the hash engine.
+    #[must_use]
+    pub fn chain(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.0.input(data.as_ref());
+        self
+    }
+
+    /// Inputs `n` into the hash engine as 4 little-endian bytes.
+    #[must_use]
+    pub fn chain_u32_le(mut self, n: u32) -> Self {
+        self.0.input_u32_le(n);
+        self
+    }
+
+    /// Inputs `n` into the hash engine as 8 little-endian bytes.
+    #[must_use]
+    pub fn chain_u64_le(mut self, n: u64) -> Self {
+        self.0.input_u64_le(n);
+        self
+    }
+
+    /// Inputs `n` into the hash engine encoded as a Bitcoin variable length integer (CompactSize).
+    #[must_use]
+    pub fn chain_varint(self, n: u64) -> Self {
+        match n {
+            0..=0xFC => self.chain([n as u8]),
+            0xFD..=0xFFFF => self.chain([0xFD]).chain((n as u16).to_le_bytes()),
+            0x10000..=0xFFFF_FFFF => self.chain([0xFE]).chain((n as u32).to_le_bytes()),
+            _ => self.chain([0xFF]).chain(n.to_le_bytes()),
+        }
+    }
+
+    /// Returns the wrapped hash engine.
+    pub fn into_engine(self) -> E { self.0 }
+
+    /// Finalizes the wrapped hash engine.
+    pub fn finalize(self) -> E::Hash { self.0.finalize() }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sha256;
+
+    #[test]
+    fn builder_matches_engine() {
+        use crate::HashEngine as _;
+
+        let got = sha256::Hash::builder()
+            .chain(b"abc")
+            .chain([0xde, 0xad])
+            .chain_u32_le(0x01020304)
+            .chain_u64_le(0x0102030405060708)
+            .finalize();
+
+        let mut engine = sha256::Hash::engine();
+        engine.input(b"abc");
+        engine.input(&[0xde, 0xad]);
+        engine.input(&[0x04, 0x03, 0x02, 0x01]);
+        engine.input(&[0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        assert_eq!(got, sha256::Hash::from_engine(engine));
+    }
+
+    #[test]
+    fn chain_varint() {
+        let tests: [(u64, &[u8]); 7] = [
+            (0, &[0x00]),
+            (0xFC, &[0xFC]),
+            (0xFD, &[0xFD, 0xFD, 0x00]),
+            (0xFFFF, &[0xFD, 0xFF, 0xFF]),
+            (0x10000, &[0xFE, 0x00, 0x00, 0x01, 0x00]),
+            (0xFFFF_FFFF, &[0xFE, 0xFF, 0xFF, 0xFF, 0xFF]),
+            (0x1_0000_0000, &[0xFF, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00]),
+        ];
+
+        for (n, want) in tests {
+            let got = sha256::Hash::builder().chain_varint(n).finalize();
+            assert_eq!(got, sha256::Hash::hash(want), "wrong encoding for {}", n);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Composition of two hash functions, hashing the digest of one with the other.
+//!
+//! [`hash160`](crate::hash160) is `RIPEMD160(SHA256(data))`, other schemes combine different hash
+//! functions the same way, e.g. `RIPEMD160(SHA512(data))`.
+
+use crate::HashEngine;
+
+/// A [`HashEngine`] that inputs data into an inner engine and finalizes to the hash of the inner
+/// digest by an outer engine, i.e. `O(I(data))`.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin_hashes::chain::Chain;
+/// use bitcoin_hashes::{ripemd160, sha512};
+///
+/// let hash = Chain::<ripemd160::HashEngine, sha512::HashEngine>::hash(b"abc");
+/// let manual = ripemd160::Hash::hash(sha512::Hash::hash(b"abc").as_byte_array());
+/// assert_eq!(hash, manual);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Chain<O: HashEngine, I: HashEngine> {
+    outer: O,
+    inner: I,
+}
+
+impl<O: HashEngine, I: HashEngine> Chain<O, I> {
+    /// Constructs a new engine from the `outer` and `inner` engines.
+    pub fn new(outer: O, inner: I) -> Self { Self { outer, inner } }
+
+    /// Hashes `data` with the inner hash function, then its digest with the outer one.
+    pub fn hash(data: &[u8]) -> O::Hash
+    where
+        O: Default,
+        I: Default,
+    {
+        let mut engine = Self::default();
+        engine.input(data);
+        engine.finalize()
+    }
+}
+
+impl<O: HashEngine + Default, I: HashEngine + Default> Default for Chain<O, I> {
+    fn default() -> Self { Self::new(O::default(), I::default()) }
+}
+
+impl<O: HashEngine, I: HashEngine> HashEngine for Chain<O, I> {
+    type Hash = O::Hash;
+    type Bytes = O::Bytes;
+    const BLOCK_SIZE: usize = I::BLOCK_SIZE;
+
+    fn input(&mut self, data: &[u8]) { self.inner.input(data) }
+    fn n_bytes_hashed(&self) -> u64 { self.inner.n_bytes_hashed() }
+    fn finalize(mut self) -> Self::Hash {
+        self.outer.input(self.inner.finalize().as_ref());
+        self.outer.finalize()
+    }
+}
+
+crate::internal_macros::impl_write!(
+    Chain<O, I>,
+    |us: &mut Chain<O, I>, buf| {
+        us.input(buf);
+        Ok(buf.len())
+    },
+    |_us| { Ok(()) },
+    O: crate::HashEngine,
+    I: crate::HashEngine
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash160, ripemd160, sha256, sha512};
+
+    type Ripemd160Sha512 = Chain<ripemd160::HashEngine, sha512::HashEngine>;
+
+    #[test]
+    fn hash160_is_chain() {
+        for data in [&b""[..], b"abc", &[0xab; 200]] {
+            let chained = Chain::<ripemd160::HashEngine, sha256::HashEngine>::hash(data);
+            assert_eq!(chained.to_byte_array(), hash160::Hash::hash(data).to_byte_array());
+        }
+    }
+
+    #[test]
+    fn ripemd160_sha512() {
+        let want = ripemd160::Hash::from_byte_array([
+            0xa1, 0xee, 0x18, 0x8f, 0x1a, 0x1e, 0x32, 0x2f, 0xf9, 0x5d, 0x43, 0x6c, 0x17, 0x18,
+            0xd8, 0x66, 0x32, 0xd6, 0x20, 0x11,
+        ]);
+        assert_eq!(Ripemd160Sha512::hash(b"abc"), want);
+
+        let mut engine = Ripemd160Sha512::default();
+        engine.input(b"a");
+        engine.input(b"bc");
+        assert_eq!(engine.n_bytes_hashed(), 3);
+        assert_eq!(engine.finalize(), want);
+    }
+}
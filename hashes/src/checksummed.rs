@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Hash engine wrapper that also computes a weak checksum of the data being hashed.
+//!
+//! Some file formats carry an XOR of all bytes next to a strong hash as a cheap secondary
+//! integrity check. This wrapper computes both in a single pass over the data.
+
+use crate::HashEngine;
+
+/// A [`HashEngine`] wrapper that accumulates the XOR of all input bytes and their number.
+///
+/// All calls are forwarded to the wrapped engine, finalizing this engine produces exactly the same
+/// hash as finalizing the wrapped engine would. Use [`Self::finalize_with_checksum`] to also get
+/// the checksum.
+#[derive(Debug, Clone)]
+pub struct ChecksummedEngine<E: HashEngine> {
+    engine: E,
+    xor: u8,
+    len: u64,
+}
+
+impl<E: HashEngine> ChecksummedEngine<E> {
+    /// Constructs a new checksummed engine wrapping `engine`.
+    ///
+    /// Only data input through this wrapper is checksummed, not data already in `engine`.
+    pub fn new(engine: E) -> Self { Self { engine, xor: 0, len: 0 } }
+
+    /// Returns the XOR of all bytes input so far.
+    pub fn xor_checksum(&self) -> u8 { self.xor }
+
+    /// Returns the number of bytes input through this wrapper so far.
+    pub fn bytes_checksummed(&self) -> u64 { self.len }
+
+    /// Returns a reference to the wrapped engine.
+    pub fn as_inner(&self) -> &E { &self.engine }
+
+    /// Finalizes the wrapped engine, returning the hash, the XOR checksum and the number of bytes
+    /// input through this wrapper.
+    pub fn finalize_with_checksum(self) -> (E::Hash, u8, u64) {
+        (self.engine.finalize(), self.xor, self.len)
+    }
+}
+
+impl<E: HashEngine + Default> Default for ChecksummedEngine<E> {
+    fn default() -> Self { Self::new(E::default()) }
+}
+
+impl<E: HashEngine> HashEngine for ChecksummedEngine<E> {
+    type Hash = E::Hash;
+    type Bytes = E::Bytes;
+    const BLOCK_SIZE: usize = E::BLOCK_SIZE;
+
+    fn input(&mut self, data: &[u8]) {
+        self.engine.input(data);
+        self.xor = data.iter().fold(self.xor, |acc, b| acc ^ b);
+        self.len += data.len() as u64;
+    }
+    fn n_bytes_hashed(&self) -> u64 { self.engine.n_bytes_hashed() }
+    fn finalize(self) -> Self::Hash { self.engine.finalize() }
+}
+
+crate::internal_macros::impl_write!(
+    ChecksummedEngine<E>,
+    |us: &mut ChecksummedEngine<E>, buf| {
+        us.input(buf);
+        Ok(buf.len())
+    },
+    |_us| { Ok(()) },
+    E: crate::HashEngine
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256;
+
+    #[test]
+    fn checksummed_engine() {
+        let data: [u8; 300] = core::array::from_fn(|i| (i * 13) as u8);
+
+        let mut engine = ChecksummedEngine::<sha256::HashEngine>::default();
+        assert_eq!(engine.xor_checksum(), 0);
+        engine.input(&data[..100]);
+        engine.input(&[]);
+        engine.input(&data[100..]);
+
+        let xor = data.iter().fold(0, |acc, b| acc ^ b);
+        assert_eq!(engine.xor_checksum(), xor);
+        assert_eq!(engine.bytes_checksummed(), 300);
+        assert_eq!(engine.n_bytes_hashed(), 300);
+        assert_eq!(engine.finalize_with_checksum(), (sha256::Hash::hash(&data), xor, 300));
+    }
+
+    #[test]
+    fn wraps_used_engine() {
+        let mut inner = sha256::HashEngine::new();
+        inner.input(b"prefix");
+
+        let mut engine = ChecksummedEngine::new(inner);
+        engine.input(&[0x0f, 0xf0, 0x01]);
+        assert_eq!(engine.n_bytes_hashed(), 9);
+        let (hash, xor, len) = engine.finalize_with_checksum();
+        assert_eq!(hash, sha256::Hash::hash(b"prefix\x0f\xf0\x01"));
+        assert_eq!((xor, len), (0xfe, 3));
+    }
+}
@@ -52,6 +52,20 @@ pub fn fixed_time_eq(a: &[u8], b: &[u8]) -> bool {
     unsafe { (::core::ptr::read_volatile(&r) & 1) == 0 }
 }
 
+/// Compares two byte arrays for equality in `const` context.
+///
+/// This is **not** constant time, use [`fixed_time_eq`] if that is required.
+pub(crate) const fn const_eq<const N: usize>(a: &[u8; N], b: &[u8; N]) -> bool {
+    let mut i = 0;
+    while i < N {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,6 +106,17 @@ fn eq_test() {
         assert!(!fixed_time_eq(&[0b00000000, 0b00000000], &[0b00000001, 0b00000000]));
         assert!(!fixed_time_eq(&[0b00000000, 0b00000000], &[0b00000001, 0b00000001]));
     }
+
+    #[test]
+    fn const_eq_test() {
+        const _: () = assert!(const_eq(&[0xab; 32], &[0xab; 32]));
+        const _: () = assert!(!const_eq(&[0xab; 32], &[0xba; 32]));
+
+        assert!(const_eq(&[], &[]));
+        assert!(const_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!const_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!const_eq(&[0, 2, 3], &[1, 2, 3]));
+    }
 }
 
 #[cfg(bench)]
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! CRC-32 (IEEE 802.3) checksum, as used by gzip and zip.
+//!
+//! **This is not a cryptographic hash function.** It only detects accidental corruption and is
+//! provided for container format interop. Do not use it where an adversary controls the input.
+
+/// The reflected IEEE polynomial.
+const POLY: u32 = 0xedb8_8320;
+
+/// Lookup tables for slicing-by-8.
+const TABLES: [[u32; 256]; 8] = make_tables();
+
+const fn make_tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0; 256]; 8];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { POLY ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < 256 {
+        let mut t = 1;
+        while t < 8 {
+            let prev = tables[t - 1][i];
+            tables[t][i] = (prev >> 8) ^ tables[0][(prev & 0xff) as usize];
+            t += 1;
+        }
+        i += 1;
+    }
+    tables
+}
+
+/// Computes the CRC-32 checksum of `data`.
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut engine = Engine::new();
+    engine.input(data);
+    engine.finalize()
+}
+
+/// Streaming CRC-32 engine.
+#[derive(Debug, Clone, Default)]
+pub struct Engine {
+    crc: u32,
+}
+
+impl Engine {
+    /// Constructs a new CRC-32 engine.
+    pub const fn new() -> Self { Engine { crc: 0 } }
+
+    /// Adds data to the engine.
+    pub fn input(&mut self, data: &[u8]) {
+        let mut crc = !self.crc;
+
+        let mut chunks = data.chunks_exact(8);
+        for chunk in &mut chunks {
+            let lo = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) ^ crc;
+            let hi = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+            crc = TABLES[7][(lo & 0xff) as usize]
+                ^ TABLES[6][((lo >> 8) & 0xff) as usize]
+                ^ TABLES[5][((lo >> 16) & 0xff) as usize]
+                ^ TABLES[4][(lo >> 24) as usize]
+                ^ TABLES[3][(hi & 0xff) as usize]
+                ^ TABLES[2][((hi >> 8) & 0xff) as usize]
+                ^ TABLES[1][((hi >> 16) & 0xff) as usize]
+                ^ TABLES[0][(hi >> 24) as usize];
+        }
+        for byte in chunks.remainder() {
+            crc = TABLES[0][((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8);
+        }
+
+        self.crc = !crc;
+    }
+
+    /// Returns the checksum of all data input so far.
+    pub fn finalize(self) -> u32 { self.crc }
+}
+
+crate::internal_macros::impl_write!(
+    Engine,
+    |us: &mut Engine, buf| {
+        us.input(buf);
+        Ok(buf.len())
+    },
+    |_us| { Ok(()) }
+);
+
+/// Computes the CRC-32 checksum and the SHA256 hash of the same data in a single pass.
+///
+/// Useful for archive formats that carry both, e.g. gzip wrapped data with a known SHA256.
+#[derive(Debug, Clone, Default)]
+pub struct DualDigest {
+    crc32: Engine,
+    sha256: crate::sha256::HashEngine,
+}
+
+impl DualDigest {
+    /// Constructs a new combined engine.
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds data to both engines.
+    pub fn input(&mut self, data: &[u8]) {
+        use crate::HashEngine as _;
+
+        self.crc32.input(data);
+        self.sha256.input(data);
+    }
+
+    /// Returns the CRC-32 checksum and the SHA256 hash of all data input so far.
+    pub fn finalize(self) -> (u32, crate::sha256::Hash) {
+        (self.crc32.finalize(), crate::sha256::Hash::from_engine(self.sha256))
+    }
+}
+
+crate::internal_macros::impl_write!(
+    DualDigest,
+    |us: &mut DualDigest, buf| {
+        us.input(buf);
+        Ok(buf.len())
+    },
+    |_us| { Ok(()) }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        assert_eq!(checksum(b""), 0);
+        assert_eq!(checksum(b"123456789"), 0xcbf4_3926);
+        assert_eq!(checksum(b"The quick brown fox jumps over the lazy dog"), 0x414f_a339);
+    }
+
+    #[test]
+    fn streaming() {
+        let data = [0x5a_u8; 100];
+        let want = 0x9748_744c;
+        assert_eq!(checksum(&data), want);
+        for split in [0, 1, 7, 8, 9, 63, 100] {
+            let mut engine = Engine::new();
+            engine.input(&data[..split]);
+            engine.input(&data[split..]);
+            assert_eq!(engine.finalize(), want);
+        }
+    }
+
+    #[test]
+    fn dual_digest() {
+        let data = [0x5a_u8; 100];
+        let mut engine = DualDigest::new();
+        engine.input(&data[..30]);
+        engine.input(&data[30..]);
+        assert_eq!(engine.finalize(), (checksum(&data), crate::sha256::Hash::hash(&data)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn dual_digest_write() {
+        let mut reader: &[u8] = b"123456789";
+        let mut engine = DualDigest::new();
+        std::io::copy(&mut reader, &mut engine).unwrap();
+        let (crc, hash) = engine.finalize();
+        assert_eq!(crc, 0xcbf4_3926);
+        assert_eq!(hash, crate::sha256::Hash::hash(b"123456789"));
+    }
+}
@@ -38,5 +38,48 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
-#[cfg(feature = "std")]
-impl std::error::Error for FromSliceError {}
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for FromSliceError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for FromSliceError {}
+    }
+}
+
+/// Attempted to parse a hash from an invalid hex string with [`crate::Hash::from_hex_ct`].
+///
+/// Does not say which character is invalid, that would defeat the purpose of parsing in constant
+/// time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FromHexCtError {
+    /// The string does not have exactly two characters per byte of the hash.
+    InvalidLength {
+        /// The expected number of characters.
+        expected: usize,
+        /// The number of bytes in the string.
+        got: usize,
+    },
+    /// The string contains at least one character that is not a hex digit.
+    InvalidChar,
+}
+
+impl fmt::Display for FromHexCtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FromHexCtError::InvalidLength { expected, got } =>
+                write!(f, "invalid hex string length {} (expected {})", got, expected),
+            FromHexCtError::InvalidChar => f.write_str("invalid hex character"),
+        }
+    }
+}
+
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for FromHexCtError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for FromHexCtError {}
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Short fingerprints of hashes, for identifying keys and other data.
+//!
+//! A fingerprint is the first `N` bytes of a hash, e.g. BIP32 identifies a parent key by the first
+//! 4 bytes of the HASH160 of its public key. Use [`Hash::fingerprint`](crate::Hash::fingerprint)
+//! to construct one.
+
+use core::fmt;
+#[cfg(feature = "hex")]
+use core::str;
+
+use crate::hmac::{Hmac, HmacEngine};
+use crate::{sha256, Hash as _, HashEngine as _};
+
+/// The HMAC key used by [`wallet_fingerprint`], the ASCII string `wallet-id`.
+pub const WALLET_FINGERPRINT_KEY: &[u8] = b"wallet-id";
+
+/// Computes a non-reversible identifier of a wallet from its `seed`.
+///
+/// The fingerprint is the first 8 bytes of `HMAC-SHA256(key = "wallet-id", message = seed)`, see
+/// [`WALLET_FINGERPRINT_KEY`]. It identifies the wallet without revealing anything about the
+/// seed, use [`keyed_wallet_fingerprint`] for a different key.
+pub fn wallet_fingerprint(seed: &[u8]) -> Fingerprint<8> {
+    keyed_wallet_fingerprint(WALLET_FINGERPRINT_KEY, seed)
+}
+
+/// Computes the first 8 bytes of `HMAC-SHA256(key, seed)`.
+///
+/// Same as [`wallet_fingerprint`] but with a custom HMAC `key`.
+pub fn keyed_wallet_fingerprint(key: &[u8], seed: &[u8]) -> Fingerprint<8> {
+    let mut engine = HmacEngine::<sha256::HashEngine>::new(key);
+    engine.input(seed);
+    Hmac::from_engine(engine).fingerprint()
+}
+
+/// The first `N` bytes of a hash.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint<const N: usize>([u8; N]);
+
+impl<const N: usize> Fingerprint<N> {
+    /// Constructs a new fingerprint from the underlying byte array.
+    pub const fn from_byte_array(bytes: [u8; N]) -> Self { Fingerprint(bytes) }
+
+    /// Returns the underlying byte array.
+    pub const fn to_byte_array(self) -> [u8; N] { self.0 }
+
+    /// Returns a reference to the underlying byte array.
+    pub const fn as_byte_array(&self) -> &[u8; N] { &self.0 }
+}
+
+impl<const N: usize> From<[u8; N]> for Fingerprint<N> {
+    fn from(bytes: [u8; N]) -> Self { Fingerprint(bytes) }
+}
+
+impl<const N: usize> From<Fingerprint<N>> for [u8; N] {
+    fn from(fingerprint: Fingerprint<N>) -> Self { fingerprint.0 }
+}
+
+impl<const N: usize> AsRef<[u8]> for Fingerprint<N> {
+    fn as_ref(&self) -> &[u8] { &self.0 }
+}
+
+impl<const N: usize> fmt::Debug for Fingerprint<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { crate::debug_hex(&self.0, f) }
+}
+
+#[cfg(feature = "hex")]
+impl<const N: usize> fmt::Display for Fingerprint<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::LowerHex::fmt(self, f) }
+}
+
+#[cfg(feature = "hex")]
+impl<const N: usize> fmt::LowerHex for Fingerprint<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use hex::DisplayHex as _;
+
+        fmt::LowerHex::fmt(&self.0.as_hex(), f)
+    }
+}
+
+#[cfg(feature = "hex")]
+impl<const N: usize> fmt::UpperHex for Fingerprint<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use hex::DisplayHex as _;
+
+        fmt::UpperHex::fmt(&self.0.as_hex(), f)
+    }
+}
+
+#[cfg(feature = "hex")]
+impl<const N: usize> str::FromStr for Fingerprint<N> {
+    type Err = hex::HexToArrayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use hex::FromHex as _;
+
+        Ok(Fingerprint(<[u8; N]>::from_hex(s)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Fingerprint<N> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            s.collect_str(self)
+        } else {
+            s.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Fingerprint<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de;
+
+        struct Visitor<const N: usize>;
+
+        impl<const N: usize> de::Visitor<'_> for Visitor<N> {
+            type Value = Fingerprint<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a {} byte fingerprint", N)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                let bytes =
+                    <[u8; N]>::try_from(v).map_err(|_| E::invalid_length(v.len(), &self))?;
+                Ok(Fingerprint(bytes))
+            }
+        }
+
+        if d.is_human_readable() {
+            d.deserialize_str(Visitor)
+        } else {
+            d.deserialize_bytes(Visitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash160;
+
+    #[test]
+    #[cfg(feature = "hex")]
+    fn bip32_fingerprint() {
+        use hex::FromHex as _;
+
+        // BIP32 test vector 1, master key.
+        let pubkey = <[u8; 33]>::from_hex(
+            "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2",
+        )
+        .unwrap();
+        let fingerprint: Fingerprint<4> = hash160::Hash::hash(&pubkey).fingerprint();
+        assert_eq!(fingerprint.to_byte_array(), [0x34, 0x42, 0x19, 0x3e]);
+        assert_eq!(fingerprint, "3442193e".parse().unwrap());
+        assert!("3442193e1b".parse::<Fingerprint<4>>().is_err());
+    }
+
+    #[test]
+    fn prefix_of_hash() {
+        let hash = sha256::Hash::hash(b"abc");
+        let fingerprint: Fingerprint<6> = hash.fingerprint();
+        assert_eq!(fingerprint.as_byte_array(), &hash.as_byte_array()[..6]);
+        assert_eq!(hash.fingerprint::<32>().to_byte_array(), hash.to_byte_array());
+        assert_eq!(hash.fingerprint::<0>().to_byte_array(), [0; 0]);
+    }
+
+    #[test]
+    fn wallet_id() {
+        // HMAC-SHA256 with key "wallet-id", computed independently.
+        let seed: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let want = [0x31, 0xcf, 0x83, 0xe1, 0xa0, 0x85, 0x6b, 0x73];
+        assert_eq!(wallet_fingerprint(&seed).to_byte_array(), want);
+        assert_eq!(keyed_wallet_fingerprint(b"wallet-id", &seed).to_byte_array(), want);
+        assert_ne!(keyed_wallet_fingerprint(b"other-id", &seed).to_byte_array(), want);
+
+        let want = [0x46, 0x47, 0x92, 0xa4, 0x10, 0x3e, 0x93, 0x76];
+        assert_eq!(wallet_fingerprint(&[]).to_byte_array(), want);
+    }
+
+    #[test]
+    #[should_panic]
+    fn longer_than_hash() { let _ = hash160::Hash::hash(b"abc").fingerprint::<21>(); }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "hex")]
+    fn fmt() {
+        use alloc::format;
+
+        let fingerprint = Fingerprint::from_byte_array([0xde, 0xad, 0xbe, 0xef, 0x01, 0x02]);
+        assert_eq!(format!("{}", fingerprint), "deadbeef0102");
+        assert_eq!(format!("{:?}", fingerprint), "deadbeef0102");
+        assert_eq!(format!("{:X}", fingerprint), "DEADBEEF0102");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        use serde_test::{assert_tokens, Configure, Token};
+
+        let fingerprint = Fingerprint::from_byte_array([0x34, 0x42, 0x19, 0x3e]);
+        assert_tokens(&fingerprint.readable(), &[Token::Str("3442193e")]);
+        assert_tokens(&fingerprint.compact(), &[Token::BorrowedBytes(&[0x34, 0x42, 0x19, 0x3e])]);
+    }
+}
@@ -15,6 +15,19 @@
     "Output of the Bitcoin HASH160 hash function. (RIPEMD160(SHA256))"
 }
 
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        const EMPTY: [u8; 20] =
+            crate::hex_to_array("b472a266d0bd89c13706a4132ccfb16f7c3b9fcb", false);
+        const ABC: [u8; 20] =
+            crate::hex_to_array("bb1be98c142444d7a56aa3981c3942a978e4dc33", false);
+        const TWO_BLOCK: [u8; 20] =
+            crate::hex_to_array("69dda8a60e0cfc2353aa776864092c0e5ccb4834", false);
+
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
+    }
+}
+
 fn from_engine(e: HashEngine) -> Hash {
     let sha2 = sha256::Hash::from_engine(e.0);
     let rmd = ripemd160::Hash::hash(sha2.as_byte_array());
@@ -47,6 +60,18 @@ fn n_bytes_hashed(&self) -> u64 { self.0.n_bytes_hashed() }
     fn finalize(self) -> Self::Hash { Hash::from_engine(self) }
 }
 
+impl Hash {
+    /// Hashes all the data read from `reader`.
+    ///
+    /// The data is streamed through the SHA256 stage so it never needs to be buffered in full.
+    #[cfg(feature = "std")]
+    pub fn hash_reader<R: std::io::Read>(mut reader: R) -> std::io::Result<Hash> {
+        let mut engine = HashEngine::new();
+        std::io::copy(&mut reader, &mut engine)?;
+        Ok(Hash::from_engine(engine))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -106,6 +131,19 @@ struct Test {
         }
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn hash_reader() {
+        use crate::hash160;
+
+        let data: std::vec::Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+        for len in [0, 1, 63, 64, 65, 8191, 8192, 10_000] {
+            let buffered = hash160::Hash::hash(&data[..len]);
+            let streamed = hash160::Hash::hash_reader(&data[..len]).expect("reading a slice");
+            assert_eq!(streamed, buffered);
+        }
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn ripemd_serde() {
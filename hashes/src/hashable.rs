@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Canonical hashing of structured data.
+//!
+//! The [`Hashable`] trait feeds a value into a [`HashEngine`], with the `derive` feature enabled it
+//! can be derived for structs so that adding a field can't silently be left out of the hash.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "derive")] {
+//! use bitcoin_hashes::{sha256, Hashable};
+//!
+//! #[derive(Hashable)]
+//! struct Header {
+//!     version: u32,
+//!     #[hashable(big_endian)]
+//!     height: u64,
+//!     #[hashable(len_prefix)]
+//!     memo: Vec<u8>,
+//!     #[hashable(skip)]
+//!     cached: Option<sha256::Hash>,
+//! }
+//!
+//! let header = Header { version: 2, height: 840_000, memo: b"memo".to_vec(), cached: None };
+//! let mut engine = sha256::Hash::engine();
+//! header.hash_into(&mut engine);
+//! let _hash = sha256::Hash::from_engine(engine);
+//! # }
+//! ```
+//!
+//! # Derive attributes
+//!
+//! Fields are hashed in declaration order using their own [`Hashable`] implementation. The
+//! following attributes change that:
+//!
+//! * `#[hashable(big_endian)]` / `#[hashable(little_endian)]`: byte order of integers, on a field
+//!   or on the struct to change the default (little-endian, as used by Bitcoin consensus code).
+//!   Applies to integers inside arrays, slices and vectors too.
+//! * `#[hashable(len_prefix)]`: on a field, prefix it with its `len()` encoded as `CompactSize`.
+//! * `#[hashable(skip)]`: on a field, leave it out of the hash.
+//! * `#[hashable(crate = "path")]`: on the struct, path to this crate if it is not a direct
+//!   dependency called `bitcoin_hashes` (e.g. `"bitcoin::hashes"`).
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::HashEngine;
+
+/// A value that can be fed into a [`HashEngine`] in a canonical way.
+pub trait Hashable {
+    /// Inputs this value into `engine`, integers as little-endian.
+    fn hash_into<E: HashEngine>(&self, engine: &mut E);
+
+    /// Inputs this value into `engine`, integers as big-endian.
+    ///
+    /// Defaults to [`Self::hash_into`], i.e. types without integers don't need to implement it.
+    fn hash_into_be<E: HashEngine>(&self, engine: &mut E) { self.hash_into(engine) }
+
+    /// Inputs each value of `data` into `engine`, integers as little-endian.
+    ///
+    /// Overridden for `u8` to input byte slices in one go.
+    fn hash_slice_into<E: HashEngine>(data: &[Self], engine: &mut E)
+    where
+        Self: Sized,
+    {
+        for item in data {
+            item.hash_into(engine);
+        }
+    }
+}
+
+/// Inputs `value` into `engine` encoded as a Bitcoin `CompactSize`.
+///
+/// This is the length prefix used by `#[hashable(len_prefix)]`.
+pub fn input_compact_size<E: HashEngine>(engine: &mut E, value: u64) {
+    engine.input(&internals::compact_size::encode(value));
+}
+
+impl Hashable for u8 {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { engine.input(&[*self]) }
+
+    fn hash_slice_into<E: HashEngine>(data: &[Self], engine: &mut E) { engine.input(data) }
+}
+
+impl Hashable for i8 {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { engine.input(&self.to_le_bytes()) }
+}
+
+impl Hashable for bool {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { engine.input(&[u8::from(*self)]) }
+}
+
+macro_rules! impl_hashable_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Hashable for $ty {
+                fn hash_into<E: HashEngine>(&self, engine: &mut E) {
+                    engine.input(&self.to_le_bytes())
+                }
+
+                fn hash_into_be<E: HashEngine>(&self, engine: &mut E) {
+                    engine.input(&self.to_be_bytes())
+                }
+            }
+        )*
+    }
+}
+impl_hashable_int!(u16, u32, u64, u128, i16, i32, i64, i128);
+
+impl<T: Hashable> Hashable for [T] {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { T::hash_slice_into(self, engine) }
+
+    fn hash_into_be<E: HashEngine>(&self, engine: &mut E) {
+        for item in self {
+            item.hash_into_be(engine);
+        }
+    }
+}
+
+impl<T: Hashable, const N: usize> Hashable for [T; N] {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { self[..].hash_into(engine) }
+
+    fn hash_into_be<E: HashEngine>(&self, engine: &mut E) { self[..].hash_into_be(engine) }
+}
+
+impl Hashable for str {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { engine.input(self.as_bytes()) }
+}
+
+impl<T: Hashable + ?Sized> Hashable for &T {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { (**self).hash_into(engine) }
+
+    fn hash_into_be<E: HashEngine>(&self, engine: &mut E) { (**self).hash_into_be(engine) }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Hashable + ?Sized> Hashable for Box<T> {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { (**self).hash_into(engine) }
+
+    fn hash_into_be<E: HashEngine>(&self, engine: &mut E) { (**self).hash_into_be(engine) }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Hashable> Hashable for Vec<T> {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { self[..].hash_into(engine) }
+
+    fn hash_into_be<E: HashEngine>(&self, engine: &mut E) { self[..].hash_into_be(engine) }
+}
+
+#[cfg(feature = "alloc")]
+impl Hashable for String {
+    fn hash_into<E: HashEngine>(&self, engine: &mut E) { self.as_str().hash_into(engine) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256;
+
+    fn engine_of<T: Hashable + ?Sized>(value: &T) -> sha256::HashEngine {
+        let mut engine = sha256::HashEngine::new();
+        value.hash_into(&mut engine);
+        engine
+    }
+
+    #[test]
+    fn ints() {
+        let mut want = sha256::HashEngine::new();
+        want.input(&[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(engine_of(&[0x0201_u16, 0x0403]), want);
+
+        let mut engine = engine_of(&0x0403_0201_u32);
+        assert_eq!(engine, want);
+
+        (-1_i8).hash_into(&mut engine);
+        true.hash_into(&mut engine);
+        want.input(&[0xff, 0x01]);
+        assert_eq!(engine, want);
+    }
+
+    #[test]
+    fn big_endian() {
+        let mut engine = sha256::HashEngine::new();
+        [0x0102_u16, 0x0304].hash_into_be(&mut engine);
+        assert_eq!(engine, engine_of(&[1_u8, 2, 3, 4]));
+    }
+
+    #[test]
+    fn compact_size() {
+        let mut engine = sha256::HashEngine::new();
+        input_compact_size(&mut engine, 0xfd);
+        input_compact_size(&mut engine, 3);
+        assert_eq!(engine, engine_of(&[0xfd_u8, 0xfd, 0x00, 0x03]));
+    }
+}
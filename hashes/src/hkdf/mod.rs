@@ -28,8 +28,14 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
-#[cfg(feature = "std")]
-impl std::error::Error for MaxLengthError {}
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for MaxLengthError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for MaxLengthError {}
+    }
+}
 
 /// HMAC-based Extract-and-Expand Key Derivation Function (HKDF).
 #[derive(Copy, Clone)]
@@ -7,12 +7,14 @@
 
 //! Hash-based Message Authentication Code (HMAC).
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::{convert, fmt, str};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{Hash, HashEngine};
+use crate::{sha256, Hash, HashEngine};
 
 /// A hash computed from a RFC 2104 HMAC. Parameterized by the underlying hash function.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -80,6 +82,55 @@ pub fn from_inner_engines(iengine: T, oengine: T) -> HmacEngine<T> {
     }
 }
 
+impl<T: Hash> Hmac<T> {
+    /// Produces the HMAC from the current state of a given engine.
+    pub fn from_engine<E: HashEngine<Hash = T>>(e: HmacEngine<E>) -> Self { e.finalize() }
+}
+
+impl Hmac<sha256::Hash> {
+    /// Computes the HMAC of each message with the key in `key_state` and checks it against the
+    /// expected value using [`crate::cmp::fixed_time_eq`].
+    ///
+    /// Returns one `bool` per pair, in order. Messages are processed two at a time with both
+    /// SHA256 computations interleaved, which is faster than verifying them one by one when the
+    /// SHA-NI instructions are available.
+    #[cfg(feature = "alloc")]
+    pub fn verify_many(
+        key_state: &HmacEngine<sha256::HashEngine>,
+        pairs: &[(&[u8], Self)],
+    ) -> Vec<bool> {
+        let verify = |hmac: sha256::Hash, expected: &Self| {
+            crate::cmp::fixed_time_eq(hmac.as_byte_array(), expected.as_byte_array())
+        };
+
+        let mut ret = Vec::with_capacity(pairs.len());
+        let mut chunks = pairs.chunks_exact(2);
+        for chunk in &mut chunks {
+            let ((msg_a, expected_a), (msg_b, expected_b)) = (&chunk[0], &chunk[1]);
+            let (ihash_a, ihash_b) = sha256::finalize_pair(
+                key_state.iengine.clone(),
+                msg_a,
+                key_state.iengine.clone(),
+                msg_b,
+            );
+            let (hmac_a, hmac_b) = sha256::finalize_pair(
+                key_state.oengine.clone(),
+                ihash_a.as_byte_array(),
+                key_state.oengine.clone(),
+                ihash_b.as_byte_array(),
+            );
+            ret.push(verify(hmac_a, expected_a));
+            ret.push(verify(hmac_b, expected_b));
+        }
+        for (msg, expected) in chunks.remainder() {
+            let mut engine = key_state.clone();
+            engine.input(msg);
+            ret.push(verify(engine.finalize().0, expected));
+        }
+        ret
+    }
+}
+
 impl<T: HashEngine> HashEngine for HmacEngine<T> {
     type Hash = Hmac<T::Hash>;
     type Bytes = T::Bytes;
@@ -106,6 +157,10 @@ impl<T: Hash + fmt::LowerHex> fmt::LowerHex for Hmac<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::LowerHex::fmt(&self.0, f) }
 }
 
+impl<T: Hash + fmt::UpperHex> fmt::UpperHex for Hmac<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::UpperHex::fmt(&self.0, f) }
+}
+
 impl<T: Hash> convert::AsRef<[u8]> for Hmac<T> {
     // Calling as_byte_array is more reliable
     fn as_ref(&self) -> &[u8] { self.0.as_byte_array().as_ref() }
@@ -152,6 +207,8 @@ fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Hmac<T>, D::Error> {
     T: crate::HashEngine
 );
 
+crate::internal_macros::impl_extend!(HmacEngine<T>, T: crate::HashEngine);
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -279,6 +336,146 @@ struct Test {
         }
     }
 
+    // RFC 4231 HMAC-SHA-512 test cases 1-4, 6 and 7. SHA-512 uses a 128 byte block, the 131 byte
+    // keys exercise the hashing of keys longer than that.
+    #[test]
+    #[cfg(feature = "hex")]
+    fn rfc4231_sha512() {
+        use crate::{sha512, HashEngine as _, HmacEngine};
+
+        let key_1_to_25: [u8; 25] = core::array::from_fn(|i| i as u8 + 1);
+        let tests: [(&[u8], &[u8], &str); 6] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+                 daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+                 9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+            (
+                &[0xaa; 20],
+                &[0xdd; 50],
+                "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39\
+                 bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+            ),
+            (
+                &key_1_to_25,
+                &[0xcd; 50],
+                "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3db\
+                 a91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+            ),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+                 6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            ),
+            (
+                &[0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size \
+                  data. The key needs to be hashed before being used by the HMAC algorithm.",
+                "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944\
+                 b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+            ),
+        ];
+
+        for (key, input, output) in tests {
+            let mut engine = HmacEngine::<sha512::HashEngine>::new(key);
+            engine.input(input);
+            assert_eq!(engine.finalize(), output.parse().unwrap());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "hex")]
+    fn fmt_roundtrips() {
+        use alloc::format;
+
+        use crate::{sha256, HashEngine as _, HmacEngine, HmacSha256};
+
+        let mut engine = HmacEngine::<sha256::HashEngine>::new(b"key");
+        engine.input(b"some arbitrary bytes");
+        let hmac = engine.finalize();
+
+        let hex = format!("{}", hmac);
+        assert_eq!(format!("{:x}", hmac), hex);
+        assert_eq!(format!("{:X}", hmac), hex.to_uppercase());
+
+        let rinsed = hex.parse::<HmacSha256>().expect("failed to parse hex");
+        assert_eq!(rinsed, hmac);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "serde")]
+    fn hmac_sha256_json_roundtrip() {
+        use alloc::format;
+
+        use crate::{sha256, HashEngine as _, HmacEngine, HmacSha256};
+
+        let mut engine = HmacEngine::<sha256::HashEngine>::new(b"key");
+        engine.input(b"some arbitrary bytes");
+        let hmac = engine.finalize();
+
+        let json = serde_json::to_string(&hmac).expect("failed to serialize");
+        assert_eq!(json, format!("\"{}\"", hmac));
+        let rinsed: HmacSha256 = serde_json::from_str(&json).expect("failed to deserialize");
+        assert_eq!(rinsed, hmac);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn verify_many() {
+        use alloc::vec::Vec;
+
+        use crate::{sha256, HashEngine as _, Hmac, HmacEngine};
+
+        let data: [u8; 300] = core::array::from_fn(|i| i as u8);
+        let lens = [0, 1, 55, 56, 63, 64, 65, 119, 120, 128, 200, 300, 17];
+
+        // Second key state has a partial block input already, exercising the unaligned path.
+        let mut unaligned = HmacEngine::<sha256::HashEngine>::new(b"key");
+        unaligned.input(b"prefix");
+        for key_state in [HmacEngine::new(b"key"), unaligned] {
+            let hmac = |msg: &[u8]| {
+                let mut engine = key_state.clone();
+                engine.input(msg);
+                engine.finalize()
+            };
+
+            let mut pairs: Vec<_> =
+                lens.iter().map(|&len| (&data[..len], hmac(&data[..len]))).collect();
+            assert!(Hmac::verify_many(&key_state, &pairs).iter().all(|ok| *ok));
+
+            let (hmac_3, hmac_4) = (pairs[3].1, pairs[4].1);
+            (pairs[3].1, pairs[4].1) = (hmac_4, hmac_3);
+            let want: Vec<_> = (0..lens.len()).map(|i| i != 3 && i != 4).collect();
+            assert_eq!(Hmac::verify_many(&key_state, &pairs), want);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn streaming_write() {
+        use crate::{sha256, HashEngine as _, Hmac, HmacEngine};
+
+        let data: [u8; 1000] = core::array::from_fn(|i| (i * 3) as u8);
+
+        let mut engine = HmacEngine::<sha256::HashEngine>::new(b"key");
+        std::io::copy(&mut &data[..], &mut engine).unwrap();
+        let streamed = Hmac::from_engine(engine);
+
+        let mut engine = HmacEngine::<sha256::HashEngine>::new(b"key");
+        engine.input(&data);
+        assert_eq!(streamed, engine.finalize());
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn hmac_sha512_serde() {
@@ -316,6 +513,27 @@ mod benches {
 
     use crate::{sha256, HashEngine as _, HmacEngine};
 
+    #[bench]
+    #[cfg(feature = "alloc")]
+    pub fn hmac_sha256_verify_many_64(bh: &mut Bencher) {
+        use alloc::vec::Vec;
+
+        use crate::Hmac;
+
+        let key_state = HmacEngine::<sha256::HashEngine>::new(b"key");
+        let msgs = [[1u8; 64]; 64];
+        let pairs: Vec<_> = msgs
+            .iter()
+            .map(|msg| {
+                let mut engine = key_state.clone();
+                engine.input(msg);
+                (&msg[..], engine.finalize())
+            })
+            .collect();
+        bh.iter(|| Hmac::verify_many(&key_state, &pairs));
+        bh.bytes = 64 * 64;
+    }
+
     #[bench]
     pub fn hmac_sha256_10(bh: &mut Bencher) {
         let mut engine = HmacEngine::<sha256::HashEngine>::new(&[]);
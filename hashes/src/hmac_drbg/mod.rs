@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! HMAC based Deterministic Random Bit Generator (HMAC-DRBG).
+//!
+//! Implementation based on NIST SP 800-90A, as used by RFC6979 for deterministic nonces. Reseed
+//! counters and per-request limits are not enforced, the caller is responsible for those if
+//! required.
+
+use core::fmt;
+
+use crate::{Hash, HashEngine, Hmac, HmacEngine};
+
+/// Maximum supported output length of the underlying hash, in bytes.
+const MAX_HASH_LEN: usize = 64;
+
+/// HMAC based Deterministic Random Bit Generator (HMAC-DRBG).
+#[derive(Clone)]
+pub struct HmacDrbg<T: HashEngine> {
+    /// HMAC engine keyed with the current `K`.
+    k: HmacEngine<T>,
+    /// The current value `V`.
+    v: Hmac<T::Hash>,
+}
+
+impl<T: HashEngine + Default> HmacDrbg<T> {
+    /// Instantiates a new DRBG.
+    ///
+    /// # Panics
+    ///
+    /// If the output of the underlying hash is larger than 64 bytes.
+    pub fn new(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Self {
+        let len = <T::Hash as Hash>::LEN;
+        assert!(len <= MAX_HASH_LEN, "hashes larger than 64 bytes are not supported");
+
+        let k = HmacEngine::new(&[0x00; MAX_HASH_LEN][..len]);
+        let v = Hmac::from_slice_exact(&[0x01; MAX_HASH_LEN][..len]).expect("correct length");
+        let mut drbg = HmacDrbg { k, v };
+        drbg.update(&[entropy, nonce, personalization]);
+        drbg
+    }
+
+    /// Reseeds the DRBG with fresh `entropy` and optional `additional` input.
+    pub fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        self.update(&[entropy, additional]);
+    }
+
+    /// Fills `out` with pseudorandom bytes.
+    pub fn generate(&mut self, out: &mut [u8]) {
+        for chunk in out.chunks_mut(<T::Hash as Hash>::LEN) {
+            self.v = self.hmac_v();
+            chunk.copy_from_slice(&self.v.as_ref()[..chunk.len()]);
+        }
+        self.update(&[]);
+    }
+
+    /// The HMAC-DRBG update function, `data` is treated as a single concatenated input.
+    fn update(&mut self, data: &[&[u8]]) {
+        for separator in [0x00, 0x01] {
+            if separator == 0x01 && data.iter().all(|d| d.is_empty()) {
+                break;
+            }
+            let mut engine = self.k.clone();
+            engine.input(self.v.as_ref());
+            engine.input(&[separator]);
+            for d in data {
+                engine.input(d);
+            }
+            self.k = HmacEngine::new(engine.finalize().as_ref());
+            self.v = self.hmac_v();
+        }
+    }
+
+    /// Returns `HMAC(K, V)`.
+    fn hmac_v(&self) -> Hmac<T::Hash> {
+        let mut engine = self.k.clone();
+        engine.input(self.v.as_ref());
+        engine.finalize()
+    }
+}
+
+impl<T: HashEngine> fmt::Debug for HmacDrbg<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str("HmacDrbg(..)") }
+}
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+#[cfg(feature = "hex")]
+mod tests {
+    use hex::prelude::{DisplayHex, FromHex};
+
+    use super::*;
+    use crate::sha256;
+
+    // NIST CAVP HMAC_DRBG.rsp, [SHA-256], no prediction resistance, COUNT = 0.
+    #[test]
+    fn nist_sha256() {
+        let entropy =
+            <[u8; 32]>::from_hex("ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488")
+                .unwrap();
+        let nonce = <[u8; 16]>::from_hex("659ba96c601dc69fc902940805ec0ca8").unwrap();
+
+        let mut drbg = HmacDrbg::<sha256::HashEngine>::new(&entropy, &nonce, &[]);
+        let mut out = [0u8; 128];
+        drbg.generate(&mut out);
+        drbg.generate(&mut out);
+
+        assert_eq!(
+            out.to_lower_hex_string(),
+            "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89\
+             d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1\
+             07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668\
+             961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8"
+        );
+    }
+
+    #[test]
+    fn reseed_changes_output() {
+        let mut a = HmacDrbg::<sha256::HashEngine>::new(&[0xab; 32], &[0xcd; 16], b"pers");
+        let mut b = a.clone();
+        b.reseed(&[0xef; 32], &[]);
+
+        let (mut out_a, mut out_b) = ([0u8; 40], [0u8; 40]);
+        a.generate(&mut out_a);
+        b.generate(&mut out_b);
+        assert_ne!(out_a, out_b);
+    }
+}
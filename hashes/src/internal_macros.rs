@@ -46,6 +46,48 @@ fn to_byte_array(self) -> Self::Bytes { self.to_byte_array() }
 
             fn as_byte_array(&self) -> &Self::Bytes { self.as_byte_array() }
         }
+
+        impl<$($gen: $gent),*> $crate::_export::_core::convert::From<Hash<$($gen),*>> for [u8; $bits / 8] {
+            #[inline]
+            fn from(hash: Hash<$($gen),*>) -> Self { hash.to_byte_array() }
+        }
+
+        impl<$($gen: $gent),*> $crate::_export::_core::cmp::PartialEq<[u8; $bits / 8]> for Hash<$($gen),*> {
+            #[inline]
+            fn eq(&self, other: &[u8; $bits / 8]) -> bool { self.as_byte_array() == other }
+        }
+
+        /// Compares the internal byte order, consistent with `Ord`.
+        impl<$($gen: $gent),*> $crate::_export::_core::cmp::PartialOrd<[u8; $bits / 8]> for Hash<$($gen),*> {
+            #[inline]
+            fn partial_cmp(&self, other: &[u8; $bits / 8]) -> Option<$crate::_export::_core::cmp::Ordering> {
+                self.as_byte_array().partial_cmp(other)
+            }
+        }
+
+        #[cfg(feature = "subtle")]
+        impl<$($gen: $gent),*> $crate::subtle::ConditionallySelectable for Hash<$($gen),*> {
+            fn conditional_select(a: &Self, b: &Self, choice: $crate::subtle::Choice) -> Self {
+                let mut bytes = [0; $bits / 8];
+                for (byte, (a, b)) in bytes.iter_mut().zip(a.as_byte_array().iter().zip(b.as_byte_array())) {
+                    *byte = $crate::subtle::ConditionallySelectable::conditional_select(a, b, choice);
+                }
+                Self::from_byte_array(bytes)
+            }
+        }
+
+        #[cfg(feature = "subtle")]
+        impl<$($gen: $gent),*> $crate::subtle::ConstantTimeEq for Hash<$($gen),*> {
+            fn ct_eq(&self, other: &Self) -> $crate::subtle::Choice {
+                $crate::subtle::ConstantTimeEq::ct_eq(&self.as_byte_array()[..], &other.as_byte_array()[..])
+            }
+        }
+
+        impl<$($gen: $gent),*> $crate::Hashable for Hash<$($gen),*> {
+            fn hash_into<E: $crate::HashEngine>(&self, engine: &mut E) {
+                engine.input(self.as_byte_array())
+            }
+        }
     }
 }
 pub(crate) use hash_trait_impls;
@@ -66,11 +108,14 @@ fn as_byte_array(&self) -> &Self::Bytes { self.as_byte_array() }
 macro_rules! general_hash_type {
     ($bits:expr, $reverse:expr, $doc:literal) => {
         /// Hashes some bytes.
-        pub fn hash(data: &[u8]) -> Hash {
+        ///
+        /// Accepts anything that can be viewed as bytes, e.g. `Vec<u8>`, `Cow<[u8]>` or
+        /// `Arc<[u8]>`.
+        pub fn hash<T: AsRef<[u8]> + ?Sized>(data: &T) -> Hash {
             use crate::HashEngine as _;
 
             let mut engine = Hash::engine();
-            engine.input(data);
+            engine.input(data.as_ref());
             engine.finalize()
         }
 
@@ -95,12 +140,21 @@ impl Hash {
             /// Produces a hash from the current state of a given engine.
             pub fn from_engine(e: HashEngine) -> Hash { from_engine(e) }
 
+            /// Produces a hash from the current state of a given engine, leaving it usable.
+            pub fn from_engine_ref(e: &HashEngine) -> Hash { from_engine(e.clone()) }
+
             /// Constructs a new engine.
             pub fn engine() -> HashEngine { Default::default() }
 
+            /// Constructs a new [`Builder`](crate::builder::Builder) for chaining input.
+            pub fn builder() -> $crate::builder::Builder<HashEngine> { Default::default() }
+
             /// Hashes some bytes.
+            ///
+            /// Accepts anything that can be viewed as bytes, e.g. `Vec<u8>`, `Cow<[u8]>` or
+            /// `Arc<[u8]>`.
             #[allow(clippy::self_named_constructors)] // Hash is a noun and a verb.
-            pub fn hash(data: &[u8]) -> Self { hash(data) }
+            pub fn hash<T: AsRef<[u8]> + ?Sized>(data: &T) -> Self { hash(data) }
 
             /// Hashes all the byte slices retrieved from the iterator together.
             pub fn hash_byte_chunks<B, I>(byte_slices: I) -> Self
@@ -110,6 +164,13 @@ pub fn hash_byte_chunks<B, I>(byte_slices: I) -> Self
             {
                 hash_byte_chunks(byte_slices)
             }
+
+            /// Hashes the bytes of this hash, i.e. runs one more round of the hash function.
+            ///
+            /// This is **not** a substitute for a key derivation function, use
+            /// [`Hkdf`](crate::hkdf::Hkdf) or a password hashing function for that.
+            #[must_use]
+            pub fn rehash(&self) -> Self { hash(&self.0) }
         }
     };
 }
@@ -123,6 +184,14 @@ macro_rules! hash_type_no_default {
         pub struct Hash([u8; $bits / 8]);
 
         impl Hash {
+            /// The hash with all bytes set to zero.
+            ///
+            /// Often used as a placeholder, e.g. the previous block hash of the genesis block.
+            pub const ZERO: Self = Hash([0x00; $bits / 8]);
+
+            /// The hash with all bytes set to `0xff`.
+            pub const MAX: Self = Hash([0xff; $bits / 8]);
+
             const fn internal_new(arr: [u8; $bits / 8]) -> Self { Hash(arr) }
 
             /// Constructs a new hash from the underlying byte array.
@@ -167,6 +236,53 @@ pub fn from_slice(
 
             /// Returns a reference to the underlying byte array.
             pub const fn as_byte_array(&self) -> &[u8; $bits / 8] { &self.0 }
+
+            /// Constructs a new hash from a byte array that is in reverse order.
+            ///
+            /// This is the inverse of [`Self::to_byte_array_reversed`].
+            pub const fn from_byte_array_reversed(bytes: [u8; $bits / 8]) -> Self {
+                Self::internal_new($crate::reverse_array(bytes))
+            }
+
+            /// Returns the underlying byte array in reverse order.
+            ///
+            /// Useful for protocols that serialize hashes backwards (e.g. txids on the wire). Note
+            /// this is independent of the display direction of the hash type.
+            pub const fn to_byte_array_reversed(self) -> [u8; $bits / 8] {
+                $crate::reverse_array(self.0)
+            }
+
+            /// Returns the hash as `0x` prefixed lowercase hex, the way EVM tooling shows `bytes`.
+            ///
+            /// The bytes are always in their natural order, regardless of the display direction of
+            /// the hash type. Use `format!("{:#X}", hash)` for uppercase hex in display order.
+            #[cfg(feature = "alloc")]
+            #[cfg(feature = "hex")]
+            pub fn to_eth_string(self) -> alloc::string::String {
+                use $crate::hex::DisplayHex as _;
+
+                alloc::format!("{:#x}", self.0.as_hex())
+            }
+
+            /// Parses a hash from a hex string in `const` context.
+            ///
+            /// The string is interpreted the same way as by `FromStr`, i.e. hash types that are
+            /// displayed backwards are parsed backwards.
+            ///
+            /// # Panics
+            ///
+            /// If `s` is not a valid hex string of the correct length. When used to initialize a
+            /// `const` this is a compile time error.
+            pub const fn from_hex_const(s: &str) -> Self {
+                Self::internal_new($crate::hex_to_array(s, $reverse))
+            }
+
+            /// Compares two hashes for equality in `const` context.
+            ///
+            /// This is **not** constant time, use [`crate::cmp::fixed_time_eq`] if that is required.
+            pub const fn const_eq(&self, other: &Self) -> bool {
+                $crate::cmp::const_eq(&self.0, &other.0)
+            }
         }
 
         $crate::internal_macros::hash_trait_impls!($bits, $reverse);
@@ -179,12 +295,144 @@ pub fn from_slice(
             },
             |_us| { Ok(()) }
         );
+
+        $crate::internal_macros::impl_extend!(HashEngine);
     };
 }
 pub(crate) use hash_type_no_default;
 
+/// Defines the `HashEngine` of a Merkle-Damgård hash function, and its `from_engine` function.
+///
+/// Intended for adding new hash functions of the MD4 family (SHA1, SHA2, RIPEMD etc.) without
+/// copying the engine boilerplate. Combine with [`general_hash_type`] for the `Hash` type.
+///
+/// Arguments:
+///
+/// * `name` - name of the hash function, used in docs
+/// * `module` - name of the module, used in `tracing` spans
+/// * `block_size` - block size of the compression function, in bytes
+/// * `state` - type and initial value (IV) of the chaining state, an array of words
+/// * `output` - hash length in bytes, the hash is the first bytes of the final state
+/// * `to_bytes` - `to_be_bytes` or `to_le_bytes`, the encoding of the state words and the
+///   message length
+/// * `length` - `u64` or `u128`, the integer type the message length in bits is encoded as
+///
+/// Generates:
+///
+/// * `const BLOCK_SIZE: usize`
+/// * `pub struct HashEngine` with the fields `buffer`, `h` (the state) and `bytes_hashed`
+/// * `HashEngine::new`, `HashEngine::midstate` (returning the state as bytes), `Default` and the
+///   [`crate::HashEngine`] impl
+/// * `fn from_engine(HashEngine) -> Hash`, doing the padding and length encoding
+///
+/// The caller provides the compression function by implementing
+/// `fn process_block(&mut self)` on `HashEngine`, which must update `h` with `buffer`.
+macro_rules! md_hash_engine {
+    (
+        name: $name:literal,
+        module: $module:ident,
+        block_size: $block_size:expr,
+        state: [$word:ty; $n:expr] = $iv:expr,
+        output: $output:expr,
+        to_bytes: $to_bytes:ident,
+        length: $length:ty $(,)?
+    ) => {
+        const BLOCK_SIZE: usize = $block_size;
+
+        #[doc = concat!("Engine to compute ", $name, " hash function.")]
+        #[derive(Debug, Clone)]
+        pub struct HashEngine {
+            buffer: [u8; BLOCK_SIZE],
+            h: [$word; $n],
+            bytes_hashed: u64,
+        }
+
+        impl HashEngine {
+            #[doc = concat!("Constructs a new ", $name, " hash engine.")]
+            pub const fn new() -> Self { Self { h: $iv, bytes_hashed: 0, buffer: [0; BLOCK_SIZE] } }
+
+            #[cfg(not(hashes_fuzz))]
+            pub(crate) fn midstate(&self) -> [u8; $output] {
+                const WORD_LEN: usize = core::mem::size_of::<$word>();
+                let mut state = [0; $n * WORD_LEN];
+                for (val, bytes) in self.h.iter().zip(state.chunks_exact_mut(WORD_LEN)) {
+                    bytes.copy_from_slice(&val.$to_bytes());
+                }
+                let mut ret = [0; $output];
+                ret.copy_from_slice(&state[..$output]);
+                ret
+            }
+
+            #[cfg(hashes_fuzz)]
+            pub(crate) fn midstate(&self) -> [u8; $output] {
+                let mut ret = [0; $output];
+                ret.copy_from_slice(&self.buffer[..$output]);
+                ret
+            }
+        }
+
+        impl Default for HashEngine {
+            fn default() -> Self { Self::new() }
+        }
+
+        impl crate::HashEngine for HashEngine {
+            type Hash = Hash;
+            type Bytes = [u8; $output];
+            const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+            fn n_bytes_hashed(&self) -> u64 { self.bytes_hashed }
+
+            crate::internal_macros::engine_input_impl!();
+
+            fn finalize(self) -> Self::Hash { Hash::from_engine(self) }
+        }
+
+        fn from_engine(mut e: HashEngine) -> Hash {
+            crate::internal_macros::trace_span!(
+                concat!(stringify!($module), "::from_engine"),
+                bytes_hashed = e.bytes_hashed
+            );
+
+            let length = <$length>::from(e.bytes_hashed).wrapping_mul(8).$to_bytes();
+            crate::md_pad(&mut e, &length);
+
+            Hash(e.midstate())
+        }
+    };
+}
+pub(crate) use md_hash_engine;
+
+/// Enters a `tracing` span at trace level for the rest of the enclosing block.
+///
+/// Expands to nothing unless the `tracing` feature is enabled.
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($($args)*).entered();
+    };
+}
+pub(crate) use trace_span;
+
+/// Implements `Extend<u8>` and `Extend<&u8>` for a hash engine type.
+macro_rules! impl_extend {
+    ($ty: ty $(, $bounded_ty: ident : $bounds: path)*) => {
+        impl<$($bounded_ty: $bounds),*> $crate::_export::_core::iter::Extend<u8> for $ty {
+            fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+                $crate::input_byte_iter(self, iter)
+            }
+        }
+
+        impl<'a, $($bounded_ty: $bounds),*> $crate::_export::_core::iter::Extend<&'a u8> for $ty {
+            fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+                $crate::input_byte_iter(self, iter.into_iter().copied())
+            }
+        }
+    };
+}
+pub(crate) use impl_extend;
+
 macro_rules! impl_write {
-    ($ty: ty, $write_fn: expr, $flush_fn: expr $(, $bounded_ty: ident : $bounds: path),*) => {
+    ($ty: ty, $write_fn: expr, $flush_fn: expr $(, $bounded_ty: ident : $bounds: path)*) => {
         // `bitcoin_io::Write` is implemented in `bitcoin_io`.
         #[cfg(feature = "std")]
         impl<$($bounded_ty: $bounds),*> std::io::Write for $ty {
@@ -198,14 +446,54 @@ fn flush(&mut self) -> std::io::Result<()> {
                 $flush_fn(self)
             }
         }
+
+        /// Hashing is CPU bound and never blocks, `poll_write` inputs the data synchronously and
+        /// always returns `Poll::Ready`. When hashing large amounts of data consider doing so
+        /// inside `spawn_blocking` to avoid starving the runtime.
+        #[cfg(feature = "tokio")]
+        impl<$($bounded_ty: $bounds),*> tokio::io::AsyncWrite for $ty where $ty: Unpin {
+            #[inline]
+            fn poll_write(
+                self: core::pin::Pin<&mut Self>,
+                _cx: &mut core::task::Context<'_>,
+                buf: &[u8],
+            ) -> core::task::Poll<std::io::Result<usize>> {
+                core::task::Poll::Ready($write_fn(self.get_mut(), buf))
+            }
+
+            #[inline]
+            fn poll_flush(
+                self: core::pin::Pin<&mut Self>,
+                _cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<std::io::Result<()>> {
+                core::task::Poll::Ready($flush_fn(self.get_mut()))
+            }
+
+            #[inline]
+            fn poll_shutdown(
+                self: core::pin::Pin<&mut Self>,
+                _cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<std::io::Result<()>> {
+                core::task::Poll::Ready(Ok(()))
+            }
+        }
     }
 }
 pub(crate) use impl_write;
 
+/// Implements `HashEngine::input` for engines with `buffer` and `bytes_hashed` fields.
+///
+/// The total input is limited to `u64::MAX` bytes, exceeding that panics in debug builds. Note
+/// that the length encoding in the padding of the SHA family limits it further to `u64::MAX / 8`
+/// bytes.
 macro_rules! engine_input_impl(
     () => (
         #[cfg(not(hashes_fuzz))]
         fn input(&mut self, mut inp: &[u8]) {
+            debug_assert!(
+                self.bytes_hashed.checked_add(inp.len() as u64).is_some(),
+                "total input to hash engine exceeds u64::MAX bytes"
+            );
 
             while !inp.is_empty() {
                 let buf_idx = $crate::incomplete_block_len(self);
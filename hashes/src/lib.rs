@@ -79,6 +79,14 @@
 #[cfg(feature = "serde")]
 pub extern crate serde;
 
+/// Constant time cryptographic implementations.
+#[cfg(feature = "subtle")]
+pub extern crate subtle;
+
+/// Types and traits for working with bytes.
+#[cfg(feature = "bytes")]
+pub extern crate bytes;
+
 #[cfg(all(test, feature = "serde"))]
 extern crate serde_test;
 #[cfg(bench)]
@@ -96,16 +104,28 @@ pub mod _core {
     }
 }
 
-#[deprecated(since = "TBD", note = "unused now that `Hash::from_slice` is deprecated")]
 mod error;
 mod internal_macros;
 
+pub mod buffered;
+pub mod builder;
+pub mod chain;
+pub mod checksummed;
 pub mod cmp;
+pub mod crc32;
+pub mod fingerprint;
 pub mod hash160;
+pub mod hashable;
 pub mod hkdf;
 pub mod hmac;
+pub mod hmac_drbg;
 #[macro_use]
 pub mod macros;
+#[cfg(feature = "alloc")]
+pub mod merkle;
+#[cfg(feature = "std")]
+pub mod metered;
+pub mod record;
 pub mod ripemd160;
 pub mod sha1;
 pub mod sha256;
@@ -115,6 +135,7 @@ pub mod _core {
 pub mod sha512;
 pub mod sha512_256;
 pub mod siphash24;
+pub mod xmd;
 
 #[deprecated(since = "0.15.0", note = "use crate::macros instead")]
 pub mod serde_macros {
@@ -133,12 +154,18 @@ pub mod serde_details {
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
 pub use self::{
+    error::{FromHexCtError, FromSliceError},
+    fingerprint::Fingerprint,
+    hashable::Hashable,
     hkdf::Hkdf,
     hmac::{Hmac, HmacEngine},
 };
 /// HASH-160: Alias for the [`hash160::Hash`] hash type.
 #[doc(inline)]
 pub use hash160::Hash as Hash160;
+/// Derive macro for [`Hashable`], see the [`hashable`] module for the supported attributes.
+#[cfg(feature = "derive")]
+pub use hashes_derive::Hashable;
 /// RIPEMD-160: Alias for the [`ripemd160::Hash`] hash type.
 #[doc(inline)]
 pub use ripemd160::Hash as Ripemd160;
@@ -164,11 +191,6 @@ pub mod serde_details {
 #[doc(inline)]
 pub use siphash24::Hash as Siphash24;
 
-/// Attempted to create a hash from an invalid length slice.
-#[deprecated(since = "TBD", note = "unused now that `Hash::from_slice` is deprecated")]
-#[allow(deprecated_in_future)]
-pub type FromSliceError = crate::error::FromSliceError; // Alias instead of re-export so we can deprecate it.
-
 /// Tagged SHA-256: Type alias for the [`sha256t::Hash`] hash type.
 pub type Sha256t<T> = sha256t::Hash<T>;
 
@@ -201,11 +223,104 @@ pub trait HashEngine: Clone {
     /// Add data to the hash engine.
     fn input(&mut self, data: &[u8]);
 
+    /// Adds `a` followed by `b` to the hash engine, as if they were concatenated.
+    ///
+    /// Intended for data split over two slices, e.g. the tail and head of a ring buffer, without
+    /// copying it into a contiguous buffer first.
+    fn input_split(&mut self, a: &[u8], b: &[u8]) {
+        self.input(a);
+        self.input(b);
+    }
+
+    /// Add a `u16` to the hash engine as 2 little-endian bytes.
+    fn input_u16_le(&mut self, n: u16) { self.input(&n.to_le_bytes()) }
+
+    /// Add a `u32` to the hash engine as 4 little-endian bytes.
+    fn input_u32_le(&mut self, n: u32) { self.input(&n.to_le_bytes()) }
+
+    /// Add a `u64` to the hash engine as 8 little-endian bytes.
+    fn input_u64_le(&mut self, n: u64) { self.input(&n.to_le_bytes()) }
+
+    /// Add a `u16` to the hash engine as 2 big-endian bytes.
+    fn input_u16_be(&mut self, n: u16) { self.input(&n.to_be_bytes()) }
+
+    /// Add a `u32` to the hash engine as 4 big-endian bytes.
+    fn input_u32_be(&mut self, n: u32) { self.input(&n.to_be_bytes()) }
+
+    /// Add a `u64` to the hash engine as 8 big-endian bytes.
+    fn input_u64_be(&mut self, n: u64) { self.input(&n.to_be_bytes()) }
+
+    /// Add each `u32` of `words` to the hash engine as 4 little-endian bytes.
+    fn input_u32_slice_le(&mut self, words: &[u32]) {
+        for &n in words {
+            self.input_u32_le(n);
+        }
+    }
+
+    /// Add each `u64` of `words` to the hash engine as 8 little-endian bytes.
+    fn input_u64_slice_le(&mut self, words: &[u64]) {
+        for &n in words {
+            self.input_u64_le(n);
+        }
+    }
+
     /// Return the number of bytes already input into the engine.
     fn n_bytes_hashed(&self) -> u64;
 
+    /// Returns the number of bytes in the current incomplete block.
+    ///
+    /// This is zero when the input so far is a multiple of [`Self::BLOCK_SIZE`], e.g. when a
+    /// midstate can be extracted.
+    fn buffered_len(&self) -> usize { incomplete_block_len(self) }
+
+    /// Returns the fraction of `total_expected` bytes input so far, clamped to `[0, 1]`.
+    ///
+    /// Intended for progress reporting when the total size is known up front, e.g. when resuming
+    /// from a midstate. Returns `1.0` if `total_expected` is zero.
+    fn progress(&self, total_expected: u64) -> f64 {
+        if total_expected == 0 {
+            return 1.0;
+        }
+        (self.n_bytes_hashed() as f64 / total_expected as f64).min(1.0)
+    }
+
+    /// Inputs all remaining bytes of `buf`, chunk by chunk, without copying them.
+    ///
+    /// Handles non-contiguous buffers (e.g. [`bytes::Buf::chain`]), the result is the same as
+    /// inputting the contiguous bytes.
+    #[cfg(feature = "bytes")]
+    fn input_buf<B: bytes::Buf>(&mut self, mut buf: B) {
+        while buf.has_remaining() {
+            let chunk = buf.chunk();
+            let len = chunk.len();
+            self.input(chunk);
+            buf.advance(len);
+        }
+    }
+
     /// Finalizes this engine.
     fn finalize(self) -> Self::Hash;
+
+    /// Finalizes this engine, also returning the number of bytes that were input into it.
+    ///
+    /// Equivalent to reading [`Self::n_bytes_hashed`] before calling [`Self::finalize`].
+    fn finalize_with_len(self) -> (Self::Hash, u64) {
+        let len = self.n_bytes_hashed();
+        (self.finalize(), len)
+    }
+}
+
+/// Known answer tests of a hash function, used by the conformance tests of this crate.
+///
+/// Every general purpose hash module implements this for its engine, so that adding a new one
+/// comes with the standard test vectors.
+#[doc(hidden)]
+pub trait TestVectors: HashEngine + Default {
+    /// Returns the test vectors as `(input, hash)` pairs.
+    ///
+    /// The hashes are the byte arrays returned by [`Hash::as_byte_array`]. Inputs are the empty
+    /// string, "abc" and the 448-bit two block message from FIPS 180.
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])];
 }
 
 /// Trait which applies to hashes of all types.
@@ -231,11 +346,196 @@ pub trait Hash:
     #[deprecated(since = "TBD", note = "use `from_byte_array` instead")]
     fn from_slice(sl: &[u8]) -> Result<Self, FromSliceError>;
 
+    /// Constructs a new hash from a byte slice that must be exactly [`Self::LEN`] bytes long.
+    ///
+    /// Intended for generic code that only has a slice at hand. Unlike the deprecated
+    /// [`Self::from_slice`] this function is here to stay, prefer [`Self::from_byte_array`] if you
+    /// already have an array.
+    ///
+    /// # Errors
+    ///
+    /// If the length of `sl` is not exactly [`Self::LEN`], the returned error carries both the
+    /// expected and the actual length.
+    fn from_slice_exact(sl: &[u8]) -> Result<Self, FromSliceError> {
+        use crate::error::FromSliceErrorInner;
+
+        match <Self::Bytes as sealed::IsByteArray>::try_from_slice(sl) {
+            Some(bytes) => Ok(Self::from_byte_array(bytes)),
+            None => Err(FromSliceError(FromSliceErrorInner { expected: Self::LEN, got: sl.len() })),
+        }
+    }
+
+    /// Parses a hash from a hex string in constant time with respect to the contents of `s`.
+    ///
+    /// The string is interpreted the same way as by `FromStr`, i.e. hash types that are displayed
+    /// backwards are parsed backwards. Both lower and upper case hex digits are accepted.
+    ///
+    /// Intended for hex strings derived from secrets, e.g. an HMAC tag received from a peer. The
+    /// `FromStr` parser returns on the first invalid character, so an attacker who can submit
+    /// strings and measure the parse time learns where the first invalid character is. This
+    /// function always processes the whole string and does not branch on or index memory by its
+    /// contents. The length of `s` is not considered secret, a string of the wrong length is
+    /// rejected immediately.
+    ///
+    /// As with [`cmp::fixed_time_eq`], this is a best effort, no compiler guarantees exist that
+    /// the generated code stays branch free. Prefer `FromStr` for public data, it is faster.
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not exactly `2 * Self::LEN` bytes long or contains any non-hex character.
+    fn from_hex_ct(s: &str) -> Result<Self, FromHexCtError> {
+        let s = s.as_bytes();
+        if s.len() != 2 * Self::LEN {
+            return Err(FromHexCtError::InvalidLength { expected: 2 * Self::LEN, got: s.len() });
+        }
+
+        let mut bytes = <Self::Bytes as sealed::IsByteArray>::zeroed();
+        if hex_decode_ct(s, sealed::IsByteArray::as_mut_slice(&mut bytes), Self::DISPLAY_BACKWARD) {
+            Ok(Self::from_byte_array(bytes))
+        } else {
+            Err(FromHexCtError::InvalidChar)
+        }
+    }
+
     /// Returns the underlying byte array.
     fn to_byte_array(self) -> Self::Bytes;
 
     /// Returns a reference to the underlying byte array.
     fn as_byte_array(&self) -> &Self::Bytes;
+
+    /// Returns `true` if the displayed hex string of this hash starts with `prefix`.
+    ///
+    /// Uses display order, i.e. for types that are displayed backwards (e.g. `sha256d`) this
+    /// checks the *last* bytes of [`Self::as_byte_array`]. `prefix` may have an odd number of
+    /// characters and is case insensitive, invalid hex never matches.
+    fn starts_with_hex(&self, prefix: &str) -> bool {
+        let bytes = self.as_byte_array().as_ref();
+        if prefix.len() > bytes.len() * 2 {
+            return false;
+        }
+        prefix.bytes().enumerate().all(|(i, c)| {
+            let index = if Self::DISPLAY_BACKWARD { bytes.len() - 1 - i / 2 } else { i / 2 };
+            let byte = bytes[index];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            (c as char).to_digit(16) == Some(u32::from(nibble))
+        })
+    }
+
+    /// Returns `true` if the underlying byte array starts with `prefix`.
+    ///
+    /// Uses the internal byte order regardless of display direction, this is the same order as
+    /// [`Self::as_byte_array`]. Faster than [`Self::starts_with_hex`].
+    fn starts_with_bytes(&self, prefix: &[u8]) -> bool {
+        self.as_byte_array().as_ref().starts_with(prefix)
+    }
+
+    /// Compares two hashes in the order of their displayed hex strings.
+    ///
+    /// The derived `Ord` compares the internal bytes, for types that are displayed backwards (e.g.
+    /// `sha256d`) this does not match the order a user sees. This ordering does.
+    fn cmp_display(&self, other: &Self) -> core::cmp::Ordering {
+        let (a, b) = (self.as_byte_array().as_ref(), other.as_byte_array().as_ref());
+        if Self::DISPLAY_BACKWARD {
+            a.iter().rev().cmp(b.iter().rev())
+        } else {
+            a.cmp(b)
+        }
+    }
+
+    /// Writes the raw bytes of this hash to `w`, exactly [`Self::LEN`] bytes in internal order.
+    #[cfg(feature = "std")]
+    fn write_to<W: std::io::Write + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(self.as_byte_array().as_ref())
+    }
+
+    /// Writes this hash to `w` as lower-hex, in display order (i.e. the same as `Display`).
+    #[cfg(feature = "std")]
+    fn write_hex_to<W: std::io::Write + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        let bytes = self.as_byte_array().as_ref();
+        let mut buf = [0; 128];
+        for start in (0..bytes.len()).step_by(64) {
+            let end = core::cmp::min(start + 64, bytes.len());
+            for (i, out) in (start..end).zip(buf.chunks_exact_mut(2)) {
+                let byte =
+                    if Self::DISPLAY_BACKWARD { bytes[bytes.len() - 1 - i] } else { bytes[i] };
+                out[0] = HEX[usize::from(byte >> 4)];
+                out[1] = HEX[usize::from(byte & 0x0f)];
+            }
+            w.write_all(&buf[..2 * (end - start)])?;
+        }
+        Ok(())
+    }
+
+    /// Folds this hash down to `N` bytes by XORing its `N` byte chunks together.
+    ///
+    /// Uses the internal byte order. `N` must divide [`Self::LEN`], this is checked in debug
+    /// builds. Otherwise the trailing partial chunk is XORed into the start of the result.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is zero.
+    fn fold_xor<const N: usize>(&self) -> [u8; N] {
+        debug_assert!(Self::LEN % N == 0, "N must divide the hash length");
+
+        let mut ret = [0; N];
+        for chunk in self.as_byte_array().as_ref().chunks(N) {
+            for (r, b) in ret.iter_mut().zip(chunk) {
+                *r ^= b;
+            }
+        }
+        ret
+    }
+
+    /// Returns the first `N` bytes of this hash as a [`Fingerprint`].
+    ///
+    /// Uses the internal byte order, e.g. `hash160::Hash::fingerprint::<4>` is a BIP32 key
+    /// fingerprint.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is larger than [`Self::LEN`].
+    fn fingerprint<const N: usize>(&self) -> Fingerprint<N> {
+        assert!(N <= Self::LEN, "fingerprint longer than the hash");
+
+        let mut ret = [0; N];
+        ret.copy_from_slice(&self.as_byte_array().as_ref()[..N]);
+        Fingerprint::from_byte_array(ret)
+    }
+
+    /// Returns a value that displays this hash as lower-hex with a space every `group` characters.
+    ///
+    /// Uses display order, the same as `Display`. A `group` of zero disables grouping.
+    fn display_grouped(&self, group: usize) -> DisplayGrouped<'_, Self> {
+        DisplayGrouped { hash: self, group }
+    }
+}
+
+/// Displays a hash as lower-hex split into space separated groups.
+///
+/// Returned by [`Hash::display_grouped`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayGrouped<'a, H: Hash> {
+    hash: &'a H,
+    group: usize,
+}
+
+impl<H: Hash> fmt::Display for DisplayGrouped<'_, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        let bytes = self.hash.as_byte_array().as_ref();
+        for i in 0..bytes.len() * 2 {
+            if self.group != 0 && i != 0 && i % self.group == 0 {
+                f.write_char(' ')?;
+            }
+            let index = if H::DISPLAY_BACKWARD { bytes.len() - 1 - i / 2 } else { i / 2 };
+            let nibble = if i % 2 == 0 { bytes[index] >> 4 } else { bytes[index] & 0x0f };
+            f.write_char(char::from(HEX[usize::from(nibble)]))?;
+        }
+        Ok(())
+    }
 }
 
 /// Ensures that a type is an array.
@@ -249,9 +549,17 @@ pub trait IsByteArray: AsRef<[u8]> + sealed::IsByteArray {
 }
 
 mod sealed {
-    pub trait IsByteArray {}
+    pub trait IsByteArray: Sized {
+        fn try_from_slice(sl: &[u8]) -> Option<Self>;
+        fn zeroed() -> Self;
+        fn as_mut_slice(&mut self) -> &mut [u8];
+    }
 
-    impl<const N: usize> IsByteArray for [u8; N] {}
+    impl<const N: usize> IsByteArray for [u8; N] {
+        fn try_from_slice(sl: &[u8]) -> Option<Self> { sl.try_into().ok() }
+        fn zeroed() -> Self { [0; N] }
+        fn as_mut_slice(&mut self) -> &mut [u8] { self }
+    }
 }
 
 fn incomplete_block_len<H: HashEngine>(eng: &H) -> usize {
@@ -261,6 +569,147 @@ fn incomplete_block_len<H: HashEngine>(eng: &H) -> usize {
     (eng.n_bytes_hashed() % block_size) as usize
 }
 
+/// Completes the last block of `engine` with the Merkle-Damgård padding.
+///
+/// Inputs a single 1 bit, then 0 bits until exactly `length` fits into the block, then `length`,
+/// the message length in bits as encoded by the hash function.
+fn md_pad<H: HashEngine>(engine: &mut H, length: &[u8]) {
+    const ZEROS: [u8; 128] = [0; 128]; // The largest block size of all our hash functions.
+    debug_assert!(H::BLOCK_SIZE <= ZEROS.len() && length.len() < H::BLOCK_SIZE);
+
+    engine.input(&[0x80]);
+    let pad_len = (2 * H::BLOCK_SIZE - length.len() - incomplete_block_len(engine)) % H::BLOCK_SIZE;
+    engine.input(&ZEROS[..pad_len]);
+    engine.input(length);
+    debug_assert_eq!(incomplete_block_len(engine), 0);
+}
+
+// Engines are routinely moved between threads, make sure no future change silently breaks that.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    enum AnyTag {}
+    impl sha256t::Tag for AnyTag {
+        const MIDSTATE: sha256::Midstate = sha256::Midstate::new([0; 32], 64);
+    }
+
+    macro_rules! assert_modules {
+        ($($module:ident),*) => {
+            $(
+                assert_send_sync::<$module::Hash>();
+                assert_send_sync::<$module::HashEngine>();
+            )*
+        };
+    }
+    assert_modules!(
+        hash160, ripemd160, sha1, sha256, sha256d, sha384, sha512, sha512_256, siphash24
+    );
+
+    assert_send_sync::<sha256::Midstate>();
+    assert_send_sync::<sha256::EngineState>();
+    assert_send_sync::<sha512::Midstate>();
+    assert_send_sync::<ripemd160::Midstate>();
+    assert_send_sync::<sha256t::Hash<AnyTag>>();
+    assert_send_sync::<sha256t::HashEngine<AnyTag>>();
+    assert_send_sync::<Hmac<sha256::Hash>>();
+    assert_send_sync::<HmacEngine<sha256::HashEngine>>();
+    assert_send_sync::<crc32::Engine>();
+};
+
+/// Inputs each byte yielded by `bytes`, batched into blocks to avoid calling `input` per byte.
+fn input_byte_iter<E: HashEngine, I: IntoIterator<Item = u8>>(engine: &mut E, bytes: I) {
+    let mut buf = [0u8; 64];
+    let mut len = 0;
+    for byte in bytes {
+        buf[len] = byte;
+        len += 1;
+        if len == buf.len() {
+            engine.input(&buf);
+            len = 0;
+        }
+    }
+    engine.input(&buf[..len]);
+}
+
+/// Returns `bytes` in reverse order, usable in `const` context.
+pub(crate) const fn reverse_array<const N: usize>(mut bytes: [u8; N]) -> [u8; N] {
+    let mut i = 0;
+    while i < N / 2 {
+        let tmp = bytes[i];
+        bytes[i] = bytes[N - 1 - i];
+        bytes[N - 1 - i] = tmp;
+        i += 1;
+    }
+    bytes
+}
+
+/// The 448-bit message spanning two blocks once padded, used in the FIPS 180 test vectors.
+const TWO_BLOCK_MESSAGE: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+/// Parses a hex string into a byte array in `const` context, panicking on invalid input.
+///
+/// If `backward` is true the string is interpreted in reverse byte order, matching `FromStr`
+/// for hash types that are displayed backwards.
+pub(crate) const fn hex_to_array<const N: usize>(s: &str, backward: bool) -> [u8; N] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("invalid hex character"),
+        }
+    }
+
+    let s = s.as_bytes();
+    if s.len() != N * 2 {
+        panic!("invalid hex string length");
+    }
+
+    let mut bytes = [0; N];
+    let mut i = 0;
+    while i < N {
+        bytes[i] = nibble(s[2 * i]) << 4 | nibble(s[2 * i + 1]);
+        i += 1;
+    }
+    if backward {
+        reverse_array(bytes)
+    } else {
+        bytes
+    }
+}
+
+/// Decodes the hex string `s` into `out` without branching on or indexing by the contents of `s`.
+///
+/// `s` must be exactly twice as long as `out`. Returns `false` if `s` contains any non-hex
+/// character, `out` then holds garbage. If `backward` is true the bytes are stored in reverse
+/// order.
+fn hex_decode_ct(s: &[u8], out: &mut [u8], backward: bool) -> bool {
+    /// Returns the value of the hex digit `c` and -1 if it is valid or 0 if it is not.
+    fn nibble(c: u8) -> (i32, i32) {
+        let c = i32::from(c);
+        // Each mask is -1 if `c` is in the range, 0 otherwise: both differences are negative only
+        // inside the range and the arithmetic shift spreads the sign bit.
+        let digit = ((0x2f - c) & (c - 0x3a)) >> 8;
+        let lower = ((0x60 - c) & (c - 0x67)) >> 8;
+        let upper = ((0x40 - c) & (c - 0x47)) >> 8;
+        let value = (digit & (c - 0x30)) | (lower & (c - 0x57)) | (upper & (c - 0x37));
+        (value, digit | lower | upper)
+    }
+
+    debug_assert_eq!(s.len(), out.len() * 2);
+    let len = out.len();
+    let mut valid = -1;
+    for (i, pair) in s.chunks_exact(2).enumerate() {
+        let (hi, hi_valid) = nibble(pair[0]);
+        let (lo, lo_valid) = nibble(pair[1]);
+        valid &= hi_valid & lo_valid;
+        // `backward` is a property of the type, not secret.
+        let index = if backward { len - 1 - i } else { i };
+        out[index] = ((hi << 4) | lo) as u8;
+    }
+    valid != 0
+}
+
 /// Writes `bytes` as a `hex` string to the formatter.
 ///
 /// For when we cannot rely on having the `hex` feature enabled. Ignores formatter options and just
@@ -295,6 +744,398 @@ mod tests {
     #[cfg(not(feature = "hex"))]
     crate::impl_debug_only_for_newtype!(TestNewtype, TestNewtype2);
 
+    #[test]
+    fn input_integers() {
+        let mut engine = sha256d::Hash::engine();
+        engine.input_u16_le(0x0102);
+        engine.input_u32_le(0x01020304);
+        engine.input_u64_le(0x0102030405060708);
+        engine.input_u16_be(0x0102);
+        engine.input_u32_be(0x01020304);
+        engine.input_u64_be(0x0102030405060708);
+
+        #[rustfmt::skip]
+        let want = sha256d::Hash::hash(&[
+            0x02, 0x01,
+            0x04, 0x03, 0x02, 0x01,
+            0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+            0x01, 0x02,
+            0x01, 0x02, 0x03, 0x04,
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        ]);
+        assert_eq!(engine.n_bytes_hashed(), 28);
+        assert_eq!(sha256d::Hash::from_engine(engine), want);
+    }
+
+    #[test]
+    fn input_integer_slices() {
+        let words32: [u32; 20] = core::array::from_fn(|i| (i as u32).wrapping_mul(0x9e37_79b9));
+        let words64: [u64; 20] =
+            core::array::from_fn(|i| (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+
+        let mut engine = sha256::Hash::engine();
+        engine.input_u32_slice_le(&words32);
+        engine.input_u64_slice_le(&words64);
+
+        let mut want = sha256::Hash::engine();
+        for n in words32 {
+            want.input(&n.to_le_bytes());
+        }
+        for n in words64 {
+            want.input(&n.to_le_bytes());
+        }
+        assert_eq!(engine.n_bytes_hashed(), 20 * 4 + 20 * 8);
+        assert_eq!(sha256::Hash::from_engine(engine), sha256::Hash::from_engine(want));
+    }
+
+    #[test]
+    fn buffered_len() {
+        let mut engine = sha256::Hash::engine();
+        assert_eq!(engine.buffered_len(), 0);
+        engine.input(&[0; 63]);
+        assert_eq!(engine.buffered_len(), 63);
+        engine.input(&[0; 2]);
+        assert_eq!(engine.buffered_len(), 1);
+        engine.input(&[0; 63]);
+        assert_eq!(engine.buffered_len(), 0);
+        assert!(engine.can_extract_midstate());
+    }
+
+    #[test]
+    fn progress() {
+        let mut engine = sha256::Hash::engine();
+        assert_eq!(engine.progress(0), 1.0);
+        assert_eq!(engine.progress(200), 0.0);
+        engine.input(&[0; 50]);
+        assert_eq!(engine.progress(200), 0.25);
+        assert_eq!(engine.progress(0), 1.0);
+
+        let resumed = sha256::HashEngine::from_midstate(sha256::Midstate::new([0; 32], 128));
+        assert_eq!(resumed.progress(256), 0.5);
+        assert_eq!(resumed.progress(100), 1.0);
+    }
+
+    #[test]
+    fn rehash() {
+        let hash = sha256::Hash::hash(b"abc");
+        assert_eq!(hash.rehash(), sha256::Hash::hash(hash.as_byte_array()));
+        assert_eq!(hash.rehash().to_byte_array(), hash.hash_again().to_byte_array());
+
+        let hash = ripemd160::Hash::hash(b"abc");
+        assert_eq!(hash.rehash(), ripemd160::Hash::hash(hash.as_byte_array()));
+
+        let hash = sha512::Hash::hash(b"abc");
+        assert_eq!(hash.rehash().rehash(), sha512::Hash::hash(hash.rehash().as_byte_array()));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "hex")]
+    fn to_eth_string() {
+        use alloc::format;
+
+        use hex::DisplayHex as _;
+
+        let hash = sha256::Hash::hash(b"abc");
+        let want = "0xba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(hash.to_eth_string(), want);
+        assert_eq!(hash.to_eth_string(), format!("{:#x}", hash));
+        assert_eq!(format!("{:#X}", hash), format!("0x{}", want[2..].to_uppercase()));
+
+        // Natural byte order, unlike `Display` of hash types displayed backwards.
+        let hash = sha256d::Hash::hash(b"abc");
+        assert_eq!(hash.to_eth_string(), format!("{:#x}", hash.as_byte_array().as_hex()));
+        assert_ne!(hash.to_eth_string(), format!("{:#x}", hash));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn hash_smart_pointers() {
+        use alloc::borrow::Cow;
+        use alloc::boxed::Box;
+        use alloc::string::String;
+        use alloc::sync::Arc;
+        use alloc::vec::Vec;
+
+        let want = sha256::Hash::hash(b"abc".as_slice());
+        let vec: Vec<u8> = b"abc".to_vec();
+        assert_eq!(sha256::Hash::hash(&vec), want);
+        assert_eq!(sha256::Hash::hash(&Cow::Borrowed(&vec[..])), want);
+        assert_eq!(sha256::Hash::hash(&Cow::<[u8]>::Owned(vec.clone())), want);
+        assert_eq!(sha256::Hash::hash(&Arc::<[u8]>::from(&vec[..])), want);
+        assert_eq!(sha256::Hash::hash(&Box::<[u8]>::from(&vec[..])), want);
+        assert_eq!(sha256::Hash::hash(&String::from("abc")), want);
+        assert_eq!(sha256::hash("abc"), want);
+        assert_eq!(sha256d::Hash::hash(&vec), sha256d::hash(b"abc"));
+
+        // Still usable where a function taking a slice is expected.
+        let hash: fn(&[u8]) -> sha256::Hash = sha256::Hash::hash;
+        assert_eq!(hash(&vec), want);
+        assert_eq!(sha256::Hash::hash(&[]), sha256::Hash::hash(b""));
+    }
+
+    #[test]
+    fn input_split() {
+        let data: [u8; 200] = core::array::from_fn(|i| i as u8);
+        for mid in [0, 1, 63, 64, 65, 130, 200] {
+            let (a, b) = data.split_at(mid);
+            let mut engine = sha256::Hash::engine();
+            engine.input_split(a, b);
+            assert_eq!(sha256::Hash::from_engine(engine), sha256::Hash::hash(&data));
+        }
+    }
+
+    #[test]
+    fn finalize_with_len() {
+        let mut engine = sha256::Hash::engine();
+        engine.input(b"abc");
+        assert_eq!(engine.finalize_with_len(), (sha256::Hash::hash(b"abc"), 3));
+
+        let mut engine = sha256d::Hash::engine();
+        engine.input(&[0; 100]);
+        assert_eq!(engine.finalize_with_len(), (sha256d::Hash::hash(&[0; 100]), 100));
+
+        let (hash, len) = hash160::Hash::engine().finalize_with_len();
+        assert_eq!(hash, hash160::Hash::hash(&[]));
+        assert_eq!(len, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn input_buf() {
+        use bytes::{Buf as _, Bytes, BytesMut};
+
+        let data: [u8; 200] = core::array::from_fn(|i| i as u8);
+        let want = sha256::Hash::hash(&data);
+
+        let mut engine = sha256::Hash::engine();
+        engine.input_buf(Bytes::copy_from_slice(&data));
+        assert_eq!(sha256::Hash::from_engine(engine), want);
+
+        let mut engine = sha256::Hash::engine();
+        engine.input_buf(BytesMut::from(&data[..]));
+        assert_eq!(sha256::Hash::from_engine(engine), want);
+
+        // Non-contiguous, chunks don't line up with the block size.
+        let buf = Bytes::copy_from_slice(&data[..3])
+            .chain(Bytes::copy_from_slice(&data[3..70]))
+            .chain(&data[70..]);
+        let mut engine = sha256::Hash::engine();
+        engine.input_buf(buf);
+        assert_eq!(sha256::Hash::from_engine(engine), want);
+
+        // Already partially consumed buffers input the remaining bytes only.
+        let mut buf = Bytes::copy_from_slice(&data);
+        buf.advance(10);
+        let mut engine = sha256::Hash::engine();
+        engine.input_buf(buf);
+        assert_eq!(sha256::Hash::from_engine(engine), sha256::Hash::hash(&data[10..]));
+    }
+
+    #[test]
+    fn cmp_byte_array() {
+        let hash = sha256::Hash::hash(b"abc");
+        let bytes = hash.to_byte_array();
+        assert_eq!(hash, bytes);
+        assert!(hash != [0; 32]);
+
+        let (mut lower, mut upper) = (bytes, bytes);
+        lower[31] -= 1;
+        upper[0] += 1;
+        assert!(hash > lower && hash < upper && hash <= bytes && hash >= bytes);
+        assert_eq!(hash.partial_cmp(&lower), Some(hash.cmp(&sha256::Hash::from_byte_array(lower))));
+
+        // Internal byte order, not display order.
+        let hash = sha256d::Hash::from_byte_array([0x01; 32]);
+        let mut bound = [0x01; 32];
+        bound[31] = 0x00;
+        bound[0] = 0xff;
+        assert!(hash < bound);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "hex")]
+    fn from_hex_ct() {
+        use alloc::string::ToString;
+
+        let hash = sha256::Hash::hash(b"abc");
+        assert_eq!(sha256::Hash::from_hex_ct(&hash.to_string()), Ok(hash));
+        assert_eq!(sha256::Hash::from_hex_ct(&hash.to_string().to_uppercase()), Ok(hash));
+
+        let hash = sha256d::Hash::hash(b"abc");
+        assert_eq!(sha256d::Hash::from_hex_ct(&hash.to_string()), Ok(hash));
+
+        let hmac = {
+            let mut engine = HmacEngine::<sha512::HashEngine>::new(b"key");
+            engine.input(b"abc");
+            engine.finalize()
+        };
+        assert_eq!(Hmac::from_hex_ct(&hmac.to_string()), Ok(hmac));
+
+        assert_eq!(
+            sha256::Hash::from_hex_ct("abcd"),
+            Err(FromHexCtError::InvalidLength { expected: 64, got: 4 })
+        );
+        let mut invalid = sha256::Hash::hash(b"abc").to_string();
+        invalid.replace_range(63.., "g");
+        assert_eq!(sha256::Hash::from_hex_ct(&invalid), Err(FromHexCtError::InvalidChar));
+        invalid.replace_range(62.., "\u{e9}");
+        assert_eq!(sha256::Hash::from_hex_ct(&invalid), Err(FromHexCtError::InvalidChar));
+    }
+
+    #[test]
+    fn hex_decode_ct_all_chars() {
+        for c in 0..=255_u8 {
+            let want = char::from(c).to_digit(16);
+            let mut out = [0];
+            let valid = hex_decode_ct(&[b'0', c], &mut out, false);
+            assert_eq!(valid, want.is_some(), "{:?}", char::from(c));
+            if let Some(want) = want {
+                assert_eq!(u32::from(out[0]), want);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_and_max() {
+        const ZERO: sha256::Hash = sha256::Hash::ZERO;
+
+        assert_eq!(ZERO.to_byte_array(), [0; 32]);
+        assert_eq!(sha256::Hash::MAX.to_byte_array(), [0xff; 32]);
+        assert_eq!(hash160::Hash::ZERO.to_byte_array(), [0; 20]);
+        assert_eq!(sha512::Hash::MAX.to_byte_array(), [0xff; 64]);
+        assert!(sha256::Hash::ZERO < sha256::Hash::MAX);
+
+        let hash = sha256::Hash::hash(b"abc");
+        let is_placeholder = match hash {
+            h if h == ZERO => true,
+            sha256::Hash::MAX => true,
+            _ => false,
+        };
+        assert!(!is_placeholder);
+        assert!(matches!(sha256::Hash::ZERO, ZERO));
+    }
+
+    #[test]
+    fn into_byte_array() {
+        fn to_array<T: Into<[u8; 32]>>(t: T) -> [u8; 32] { t.into() }
+
+        let hash = sha256::Hash::hash(b"abc");
+        let array: [u8; 32] = hash.into();
+        assert_eq!(array, hash.to_byte_array());
+        assert_eq!(
+            to_array(sha256d::Hash::hash(b"abc")),
+            sha256d::Hash::hash(b"abc").to_byte_array()
+        );
+
+        let array: [u8; 20] = hash160::Hash::hash(b"abc").into();
+        assert_eq!(array, hash160::Hash::hash(b"abc").to_byte_array());
+    }
+
+    #[test]
+    #[cfg(feature = "subtle")]
+    fn subtle_select_and_eq() {
+        use subtle::{Choice, ConditionallySelectable as _, ConstantTimeEq as _};
+
+        let a = sha256::Hash::hash(b"a");
+        let b = sha256::Hash::hash(b"b");
+        assert_eq!(sha256::Hash::conditional_select(&a, &b, Choice::from(0)), a);
+        assert_eq!(sha256::Hash::conditional_select(&a, &b, Choice::from(1)), b);
+
+        assert!(bool::from(a.ct_eq(&a)));
+        assert!(!bool::from(a.ct_eq(&b)));
+    }
+
+    #[test]
+    fn from_engine_ref() {
+        let mut engine = sha256d::Hash::engine();
+        engine.input(b"some data");
+        let checkpoint = sha256d::Hash::from_engine_ref(&engine);
+        assert_eq!(checkpoint, sha256d::Hash::hash(b"some data"));
+
+        engine.input(b" and some more");
+        let want = sha256d::Hash::hash(b"some data and some more");
+        assert_eq!(sha256d::Hash::from_engine(engine), want);
+    }
+
+    #[test]
+    fn cmp_display() {
+        use core::cmp::Ordering;
+
+        let a = sha256d::Hash::from_byte_array([0x01; 32]);
+        let mut bytes = [0x00; 32];
+        bytes[31] = 0x02;
+        let b = sha256d::Hash::from_byte_array(bytes);
+
+        // Internally `b` sorts first, displayed backwards it sorts last.
+        assert_eq!(b.cmp(&a), Ordering::Less);
+        assert_eq!(b.cmp_display(&a), Ordering::Greater);
+
+        let a = sha256::Hash::from_byte_array([0x01; 32]);
+        let b = sha256::Hash::from_byte_array(bytes);
+        assert_eq!(b.cmp_display(&a), b.cmp(&a));
+    }
+
+    #[test]
+    fn starts_with() {
+        let mut bytes = [0; 32];
+        bytes[0] = 0xab;
+        bytes[31] = 0xcd;
+
+        let hash = sha256::Hash::from_byte_array(bytes);
+        assert!(hash.starts_with_hex("ab0"));
+        assert!(hash.starts_with_hex("AB"));
+        assert!(!hash.starts_with_hex("cd"));
+        assert!(!hash.starts_with_hex("ax"));
+        assert!(hash.starts_with_bytes(&[0xab, 0x00]));
+
+        // sha256d is displayed backwards.
+        let hash = sha256d::Hash::from_byte_array(bytes);
+        assert!(hash.starts_with_hex("cd0"));
+        assert!(!hash.starts_with_hex("ab"));
+        assert!(hash.starts_with_bytes(&[0xab]));
+
+        assert!(hash.starts_with_hex(""));
+        let too_long = core::str::from_utf8(&[b'0'; 65]).unwrap();
+        assert!(!hash.starts_with_hex(too_long));
+    }
+
+    #[test]
+    fn extend() {
+        let data: [u8; 150] = core::array::from_fn(|i| i as u8);
+
+        let mut engine = sha256d::Hash::engine();
+        engine.extend(data.iter().copied());
+        assert_eq!(sha256d::Hash::from_engine(engine), sha256d::Hash::hash(&data));
+
+        let mut engine = HmacEngine::<sha256::HashEngine>::new(b"key");
+        engine.extend(&data[..]);
+        let mut want = HmacEngine::<sha256::HashEngine>::new(b"key");
+        want.input(&data);
+        assert_eq!(engine.finalize(), want.finalize());
+    }
+
+    #[test]
+    fn from_slice_exact() {
+        fn roundtrip<H: Hash>(sl: &[u8]) -> Result<H, FromSliceError> { H::from_slice_exact(sl) }
+
+        let hash = sha256d::Hash::hash(b"some data");
+        assert_eq!(roundtrip::<sha256d::Hash>(hash.as_ref()), Ok(hash));
+        assert_eq!(
+            roundtrip::<TestNewtype>(hash.as_ref()),
+            Ok(TestNewtype::from_byte_array(hash.to_byte_array()))
+        );
+
+        let err = roundtrip::<sha256d::Hash>(&hash.as_byte_array()[..31]).unwrap_err();
+        assert_eq!(err.expected_length(), 32);
+        assert_eq!(err.invalid_length(), 31);
+
+        let err = sha256d::Hash::from_slice_exact(&[0; 33]).unwrap_err();
+        assert_eq!(err.expected_length(), 32);
+        assert_eq!(err.invalid_length(), 33);
+    }
+
     #[test]
     #[cfg(feature = "alloc")]
     #[cfg(feature = "hex")]
@@ -314,4 +1155,115 @@ fn newtype_fmt_roundtrip() {
         let rinsed = hex.parse::<TestNewtype>().expect("failed to parse hex");
         assert_eq!(rinsed, orig)
     }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn async_write() {
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake, Waker};
+
+        use tokio::io::AsyncWrite;
+
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+
+        let data = [0xab_u8; 200];
+        let mut engine = sha256::Hash::engine();
+        for chunk in data.chunks(33) {
+            let poll = core::pin::Pin::new(&mut engine).poll_write(&mut cx, chunk);
+            assert!(matches!(poll, Poll::Ready(Ok(n)) if n == chunk.len()));
+        }
+        assert!(core::pin::Pin::new(&mut engine).poll_shutdown(&mut cx).is_ready());
+        assert_eq!(sha256::Hash::from_engine(engine), sha256::Hash::hash(&data));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[cfg(feature = "hex")]
+    fn write_to() {
+        use std::string::ToString;
+        use std::vec::Vec;
+
+        let hash = sha256d::Hash::hash(b"some data");
+        let mut raw = Vec::new();
+        hash.write_to(&mut raw).unwrap();
+        assert_eq!(raw.len(), sha256d::Hash::LEN);
+        assert_eq!(raw, hash.as_byte_array());
+
+        let mut hex = Vec::new();
+        hash.write_hex_to(&mut hex).unwrap();
+        assert_eq!(hex, hash.to_string().as_bytes());
+
+        let hash = sha512::Hash::hash(b"some data");
+        let mut hex = Vec::new();
+        hash.write_hex_to(&mut hex).unwrap();
+        assert_eq!(hex, hash.to_string().as_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "hex")]
+    fn display_grouped() {
+        use alloc::format;
+        use alloc::string::ToString;
+
+        let hash = sha256d::Hash::hash(b"some data");
+        let hex = hash.to_string();
+
+        let grouped = format!("{}", hash.display_grouped(8));
+        assert_eq!(grouped.split(' ').count(), 8);
+        assert_eq!(grouped.replace(' ', ""), hex);
+        assert_eq!(&grouped[..9], format!("{} ", &hex[..8]));
+
+        assert_eq!(format!("{}", hash.display_grouped(0)), hex);
+        assert_eq!(format!("{}", hash.display_grouped(64)), hex);
+        assert_eq!(format!("{}", hash.display_grouped(5)).split(' ').count(), 13);
+    }
+
+    #[test]
+    fn fold_xor() {
+        let hash = sha256::Hash::hash(b"some data");
+        let bytes = hash.to_byte_array();
+
+        let folded: [u8; 16] = hash.fold_xor();
+        for i in 0..16 {
+            assert_eq!(folded[i], bytes[i] ^ bytes[i + 16]);
+        }
+
+        let folded: [u8; 8] = hash.fold_xor();
+        for i in 0..8 {
+            assert_eq!(folded[i], bytes[i] ^ bytes[i + 8] ^ bytes[i + 16] ^ bytes[i + 24]);
+        }
+
+        assert_eq!(hash.fold_xor::<32>(), bytes);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn fold_xor_not_divisor() { let _ = sha256::Hash::hash(b"some data").fold_xor::<5>(); }
+
+    internals::rust_version::rust_version! {
+        if >= 1.81 {
+            #[test]
+            fn errors_impl_core_error() {
+                fn assert_error<E: core::error::Error>() {}
+
+                assert_error::<FromHexCtError>();
+                assert_error::<FromSliceError>();
+                assert_error::<hkdf::MaxLengthError>();
+                assert_error::<record::RecordLengthError>();
+                assert_error::<ripemd160::MidstateError>();
+                assert_error::<sha256::MidstateError>();
+                assert_error::<sha256::EngineStateError>();
+                assert_error::<sha256::HexError>();
+                assert_error::<sha512::MidstateError>();
+                assert_error::<xmd::XmdError>();
+            }
+        }
+    }
 }
@@ -6,6 +6,7 @@
 //! - [`hash_newtype`](crate::hash_newtype)
 //! - [`impl_hex_for_newtype`](crate::impl_hex_for_newtype)
 //! - [`impl_serde_for_newtype`](crate::impl_serde_for_newtype)
+//! - [`sha256::hash_all`](crate::sha256::hash_all)
 
 /// Macro used to define a tag.
 ///
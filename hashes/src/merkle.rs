@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Bitcoin style Merkle trees of [`sha256d`] hashes.
+//!
+//! Each node is the SHA256d of the concatenation of its two children. A level with an odd number of
+//! nodes has its last node paired with itself, this is the rule used for the transaction Merkle
+//! root in block headers.
+
+use alloc::vec::Vec;
+
+use crate::{sha256d, HashEngine as _};
+
+/// Computes the Merkle root of `leaves`.
+///
+/// Returns `None` if `leaves` is empty.
+pub fn root(leaves: &[sha256d::Hash]) -> Option<sha256d::Hash> {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        for i in 0..(level.len() + 1) / 2 {
+            let left = level[2 * i];
+            let right = level.get(2 * i + 1).copied().unwrap_or(left);
+            level[i] = combine(&left, &right);
+        }
+        level.truncate((level.len() + 1) / 2);
+    }
+    level.first().copied()
+}
+
+/// Returns an iterator over all levels of the Merkle tree of `leaves`.
+///
+/// The first item is `leaves` itself, each following item is the level above the previous one and
+/// the last item is the single element level holding the [`root`]. Yields nothing if `leaves` is
+/// empty.
+///
+/// Having every level is useful to build inclusion proofs for many leaves without recomputing the
+/// tree.
+pub fn levels(leaves: &[sha256d::Hash]) -> impl Iterator<Item = Vec<sha256d::Hash>> {
+    let first = if leaves.is_empty() { None } else { Some(leaves.to_vec()) };
+    core::iter::successors(first, |level| {
+        if level.len() == 1 {
+            return None;
+        }
+        let parent = level
+            .chunks(2)
+            .map(|pair| combine(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        Some(parent)
+    })
+}
+
+/// An append-only Merkle tree that only stores the roots of its complete subtrees.
+///
+/// Pushing a leaf does at most `log2(n)` hash operations, computing the [`root`](Self::root) of
+/// `n` leaves does at most `2 * log2(n)`. The root is the same as the one [`root`] computes over
+/// all leaves pushed so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accumulator {
+    /// The roots of the complete subtrees and their heights, in strictly decreasing height order.
+    peaks: Vec<(u32, sha256d::Hash)>,
+    /// The number of leaves pushed.
+    len: u64,
+}
+
+impl Accumulator {
+    /// Constructs a new empty accumulator.
+    pub const fn new() -> Self { Accumulator { peaks: Vec::new(), len: 0 } }
+
+    /// Appends `leaf` to the tree.
+    pub fn push(&mut self, leaf: sha256d::Hash) {
+        let mut height = 0;
+        let mut node = leaf;
+        while let Some(&(peak_height, peak)) = self.peaks.last() {
+            if peak_height != height {
+                break;
+            }
+            self.peaks.pop();
+            node = combine(&peak, &node);
+            height += 1;
+        }
+        self.peaks.push((height, node));
+        self.len += 1;
+    }
+
+    /// Returns the Merkle root of all leaves pushed so far.
+    ///
+    /// Returns `None` if no leaves were pushed.
+    pub fn root(&self) -> Option<sha256d::Hash> {
+        let mut peaks = self.peaks.iter().rev();
+        let &(mut height, mut node) = peaks.next()?;
+        for &(peak_height, peak) in peaks {
+            // Incomplete subtrees are filled up by pairing nodes with themselves.
+            while height < peak_height {
+                node = combine(&node, &node);
+                height += 1;
+            }
+            node = combine(&peak, &node);
+            height += 1;
+        }
+        Some(node)
+    }
+
+    /// Returns the number of leaves pushed.
+    pub const fn len(&self) -> u64 { self.len }
+
+    /// Returns `true` if no leaves were pushed.
+    pub const fn is_empty(&self) -> bool { self.len == 0 }
+}
+
+/// Computes the parent node of `left` and `right`.
+fn combine(left: &sha256d::Hash, right: &sha256d::Hash) -> sha256d::Hash {
+    let mut engine = sha256d::Hash::engine();
+    engine.input(left.as_byte_array());
+    engine.input(right.as_byte_array());
+    sha256d::Hash::from_engine(engine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The transactions of block 100000.
+    const TXIDS: [sha256d::Hash; 4] = [
+        sha256d::Hash::from_hex_const(
+            "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        ),
+        sha256d::Hash::from_hex_const(
+            "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        ),
+        sha256d::Hash::from_hex_const(
+            "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        ),
+        sha256d::Hash::from_hex_const(
+            "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+        ),
+    ];
+    const ROOT: sha256d::Hash = sha256d::Hash::from_hex_const(
+        "f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766",
+    );
+
+    #[test]
+    fn block_100000() {
+        assert_eq!(root(&TXIDS), Some(ROOT));
+
+        let levels: Vec<_> = levels(&TXIDS).collect();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[0], TXIDS);
+        assert_eq!(levels[1], [combine(&TXIDS[0], &TXIDS[1]), combine(&TXIDS[2], &TXIDS[3])]);
+        assert_eq!(levels[2], [ROOT]);
+    }
+
+    #[test]
+    fn odd_levels() {
+        let leaves = &TXIDS[..3];
+        let levels: Vec<_> = levels(leaves).collect();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[1][1], combine(&leaves[2], &leaves[2]));
+        assert_eq!(levels[2][0], combine(&levels[1][0], &levels[1][1]));
+
+        let leaves: Vec<_> = (0..23_u8).map(|i| sha256d::Hash::hash(&[i])).collect();
+        for n in 1..=leaves.len() {
+            let levels: Vec<_> = super::levels(&leaves[..n]).collect();
+            assert_eq!(levels.last().unwrap().len(), 1);
+            assert_eq!(levels.last().unwrap().first().copied(), root(&leaves[..n]));
+            for pair in levels.windows(2) {
+                assert_eq!(pair[1].len(), (pair[0].len() + 1) / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn accumulator() {
+        let leaves: Vec<_> = (0..33_u8).map(|i| sha256d::Hash::hash(&[i])).collect();
+
+        let mut acc = Accumulator::new();
+        assert!(acc.is_empty());
+        assert_eq!(acc.root(), None);
+        for (n, leaf) in leaves.iter().enumerate() {
+            acc.push(*leaf);
+            assert_eq!(acc.len(), n as u64 + 1);
+            assert_eq!(acc.root(), root(&leaves[..=n]));
+        }
+
+        let mut acc = Accumulator::default();
+        TXIDS.iter().for_each(|txid| acc.push(*txid));
+        assert_eq!(acc.root(), Some(ROOT));
+    }
+
+    #[test]
+    fn single_and_empty() {
+        assert_eq!(root(&TXIDS[..1]), Some(TXIDS[0]));
+        assert_eq!(levels(&TXIDS[..1]).collect::<Vec<_>>(), [TXIDS[..1].to_vec()]);
+
+        assert_eq!(root(&[]), None);
+        assert_eq!(levels(&[]).count(), 0);
+    }
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Hash engine wrapper that records metrics about the data being hashed.
+//!
+//! Useful for daemons that want to export statistics such as "bytes hashed per second" without
+//! having to write their own wrapper around each engine type.
+
+use std::time::Instant;
+
+use crate::HashEngine;
+
+/// A [`HashEngine`] wrapper that records how much data was input and when.
+///
+/// All calls are forwarded to the wrapped engine, finalizing this engine produces exactly the same
+/// hash as finalizing the wrapped engine would.
+#[derive(Debug, Clone)]
+pub struct MeteredEngine<E: HashEngine> {
+    engine: E,
+    last_input: Option<Instant>,
+}
+
+impl<E: HashEngine> MeteredEngine<E> {
+    /// Constructs a new metered engine wrapping `engine`.
+    pub fn new(engine: E) -> Self { Self { engine, last_input: None } }
+
+    /// Returns the total number of bytes input into the wrapped engine.
+    pub fn bytes_hashed(&self) -> u64 { self.engine.n_bytes_hashed() }
+
+    /// Returns the time of the last call to [`HashEngine::input`], `None` if it was never called.
+    pub fn last_input(&self) -> Option<Instant> { self.last_input }
+
+    /// Returns a reference to the wrapped engine.
+    pub fn as_inner(&self) -> &E { &self.engine }
+
+    /// Returns the wrapped engine, discarding the metrics.
+    pub fn into_inner(self) -> E { self.engine }
+}
+
+impl<E: HashEngine + Default> Default for MeteredEngine<E> {
+    fn default() -> Self { Self::new(E::default()) }
+}
+
+impl<E: HashEngine> HashEngine for MeteredEngine<E> {
+    type Hash = E::Hash;
+    type Bytes = E::Bytes;
+    const BLOCK_SIZE: usize = E::BLOCK_SIZE;
+
+    fn input(&mut self, data: &[u8]) {
+        self.engine.input(data);
+        self.last_input = Some(Instant::now());
+    }
+    fn n_bytes_hashed(&self) -> u64 { self.engine.n_bytes_hashed() }
+    fn finalize(self) -> Self::Hash { self.engine.finalize() }
+}
+
+crate::internal_macros::impl_write!(
+    MeteredEngine<E>,
+    |us: &mut MeteredEngine<E>, buf| {
+        us.input(buf);
+        Ok(buf.len())
+    },
+    |_us| { Ok(()) },
+    E: crate::HashEngine
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256;
+
+    #[test]
+    fn metered_engine() {
+        let mut engine = MeteredEngine::<sha256::HashEngine>::default();
+        assert_eq!(engine.bytes_hashed(), 0);
+        assert!(engine.last_input().is_none());
+
+        let before = Instant::now();
+        engine.input(&[0xab; 100]);
+        engine.input(&[0xcd; 28]);
+        assert_eq!(engine.bytes_hashed(), 128);
+        assert!(engine.last_input().expect("input was called") >= before);
+
+        let mut data = [0xab; 128];
+        data[100..].copy_from_slice(&[0xcd; 28]);
+        assert_eq!(engine.finalize(), sha256::Hash::hash(&data));
+    }
+}
@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Hash engine wrapper for record-oriented data.
+//!
+//! Structured logs and similar streams consist of records of a fixed size. Hashing them with a
+//! plain [`HashEngine`] makes it easy to accidentally input a partial record, this wrapper only
+//! accepts whole records.
+
+use core::fmt;
+
+use crate::HashEngine;
+
+/// Error returned when the input is not a whole number of records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordLengthError {
+    record_len: usize,
+    len: usize,
+}
+
+impl RecordLengthError {
+    /// Returns the length of a single record.
+    pub fn record_len(&self) -> usize { self.record_len }
+
+    /// Returns the length of the rejected input.
+    pub fn invalid_len(&self) -> usize { self.len }
+}
+
+impl fmt::Display for RecordLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes is not a whole number of {} byte records", self.len, self.record_len)
+    }
+}
+
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for RecordLengthError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for RecordLengthError {}
+    }
+}
+
+/// A wrapper around a [`HashEngine`] accepting only whole records of `R` bytes.
+///
+/// Hashing records with this engine produces exactly the same hash as inputting their
+/// concatenation into the wrapped engine.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin_hashes::record::RecordEngine;
+/// use bitcoin_hashes::sha256;
+///
+/// let mut engine = RecordEngine::<sha256::HashEngine, 4>::default();
+/// engine.input_record(b"abcd");
+/// engine.input_records(&[*b"efgh", *b"ijkl"]);
+/// assert!(engine.try_input(b"mnopq").is_err());
+/// assert_eq!(engine.records_hashed(), 3);
+/// assert_eq!(engine.finalize(), sha256::Hash::hash(b"abcdefghijkl"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordEngine<E: HashEngine, const R: usize> {
+    engine: E,
+    records: u64,
+}
+
+impl<E: HashEngine, const R: usize> RecordEngine<E, R> {
+    /// Constructs a new record engine wrapping `engine`.
+    ///
+    /// Data already input into `engine` is not counted in [`Self::records_hashed`].
+    ///
+    /// # Panics
+    ///
+    /// If `R` is zero.
+    pub fn new(engine: E) -> Self {
+        assert!(R > 0, "records must not be empty");
+        Self { engine, records: 0 }
+    }
+
+    /// Inputs a single record.
+    pub fn input_record(&mut self, record: &[u8; R]) {
+        self.engine.input(record);
+        self.records += 1;
+    }
+
+    /// Inputs a sequence of records.
+    pub fn input_records(&mut self, records: &[[u8; R]]) {
+        for record in records {
+            self.input_record(record);
+        }
+    }
+
+    /// Inputs `data` consisting of any number of concatenated records.
+    ///
+    /// The whole of `data` is passed to the wrapped engine in a single call, so long runs of
+    /// records are compressed block by block without buffering.
+    ///
+    /// # Errors
+    ///
+    /// If the length of `data` is not a multiple of `R`, nothing is input in that case.
+    pub fn try_input(&mut self, data: &[u8]) -> Result<(), RecordLengthError> {
+        if data.len() % R != 0 {
+            return Err(RecordLengthError { record_len: R, len: data.len() });
+        }
+        self.engine.input(data);
+        self.records += (data.len() / R) as u64;
+        Ok(())
+    }
+
+    /// Returns the number of records input through this wrapper so far.
+    pub fn records_hashed(&self) -> u64 { self.records }
+
+    /// Returns a reference to the wrapped engine.
+    pub fn as_inner(&self) -> &E { &self.engine }
+
+    /// Returns the wrapped engine.
+    pub fn into_inner(self) -> E { self.engine }
+
+    /// Finalizes the wrapped engine, returning the hash of all data input so far.
+    pub fn finalize(self) -> E::Hash { self.engine.finalize() }
+}
+
+impl<E: HashEngine + Default, const R: usize> Default for RecordEngine<E, R> {
+    fn default() -> Self { Self::new(E::default()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256;
+
+    #[test]
+    fn matches_concatenation() {
+        let data: [u8; 21 * 40] = core::array::from_fn(|i| (i * 7) as u8);
+        let (records, rest) = internals::slice::SliceExt::bitcoin_as_chunks::<21>(&data[..]);
+        assert!(rest.is_empty());
+
+        let mut one_by_one = RecordEngine::<sha256::HashEngine, 21>::default();
+        for record in records {
+            one_by_one.input_record(record);
+        }
+        let mut batched = RecordEngine::<sha256::HashEngine, 21>::default();
+        batched.input_records(&records[..3]);
+        batched.try_input(&data[63..]).unwrap();
+
+        assert_eq!(one_by_one.records_hashed(), 40);
+        assert_eq!(batched.records_hashed(), 40);
+        assert_eq!(batched.as_inner().n_bytes_hashed(), 840);
+        assert_eq!(one_by_one.finalize(), sha256::Hash::hash(&data));
+        assert_eq!(batched.finalize(), sha256::Hash::hash(&data));
+    }
+
+    #[test]
+    fn partial_record() {
+        let mut engine = RecordEngine::<sha256::HashEngine, 8>::default();
+        let err = engine.try_input(&[0; 12]).unwrap_err();
+        assert_eq!((err.record_len(), err.invalid_len()), (8, 12));
+        assert_eq!(engine.records_hashed(), 0);
+        assert_eq!(engine.into_inner().n_bytes_hashed(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_records() { let _ = RecordEngine::<sha256::HashEngine, 0>::default(); }
+}
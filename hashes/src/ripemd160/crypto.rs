@@ -130,6 +130,11 @@ macro_rules! process_block(
 
 impl HashEngine {
     pub(super) fn process_block(&mut self) {
+        crate::internal_macros::trace_span!(
+            "ripemd160::process_block",
+            bytes_hashed = self.bytes_hashed,
+            backend = "software"
+        );
         debug_assert_eq!(self.buffer.len(), BLOCK_SIZE);
 
         let mut w = [0u32; 16];
@@ -10,9 +10,7 @@
 #[cfg(bench)]
 mod tests;
 
-use core::cmp;
-
-use crate::{incomplete_block_len, HashEngine as _};
+use core::{cmp, convert, fmt};
 
 crate::internal_macros::general_hash_type! {
     160,
@@ -20,29 +18,32 @@
     "Output of the RIPEMD160 hash function."
 }
 
-#[cfg(not(hashes_fuzz))]
-fn from_engine(mut e: HashEngine) -> Hash {
-    // pad buffer with a single 1-bit then all 0s, until there are exactly 8 bytes remaining
-    let n_bytes_hashed = e.bytes_hashed;
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        const EMPTY: [u8; 20] =
+            crate::hex_to_array("9c1185a5c5e9fc54612808977ee8f548b2258d31", false);
+        const ABC: [u8; 20] =
+            crate::hex_to_array("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc", false);
+        const TWO_BLOCK: [u8; 20] =
+            crate::hex_to_array("12a053384a9c0c88e405a06c27dcf49ada62eb2b", false);
 
-    let zeroes = [0; BLOCK_SIZE - 8];
-    e.input(&[0x80]);
-    if crate::incomplete_block_len(&e) > zeroes.len() {
-        e.input(&zeroes);
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
     }
-    let pad_length = zeroes.len() - incomplete_block_len(&e);
-    e.input(&zeroes[..pad_length]);
-    debug_assert_eq!(incomplete_block_len(&e), zeroes.len());
+}
+
+#[cfg(not(hashes_fuzz))]
+fn from_engine(mut e: HashEngine) -> Hash {
+    crate::internal_macros::trace_span!("ripemd160::from_engine", bytes_hashed = e.bytes_hashed);
 
-    e.input(&(8 * n_bytes_hashed).to_le_bytes());
-    debug_assert_eq!(incomplete_block_len(&e), 0);
+    let length = e.bytes_hashed.wrapping_mul(8).to_le_bytes();
+    crate::md_pad(&mut e, &length);
 
-    Hash(e.midstate())
+    Hash(e.midstate_unchecked().bytes)
 }
 
 #[cfg(hashes_fuzz)]
 fn from_engine(e: HashEngine) -> Hash {
-    let mut res = e.midstate();
+    let mut res = e.midstate_unchecked().bytes;
     res[0] ^= (e.bytes_hashed & 0xff) as u8;
     Hash(res)
 }
@@ -66,28 +67,63 @@ pub const fn new() -> Self {
             buffer: [0; BLOCK_SIZE],
         }
     }
+}
+
+impl Default for HashEngine {
+    fn default() -> Self { Self::new() }
+}
 
+impl HashEngine {
+    /// Constructs a new [`HashEngine`] from a [`Midstate`].
+    ///
+    /// Please see docs on [`Midstate`] before using this function.
+    pub fn from_midstate(midstate: Midstate) -> HashEngine {
+        let mut ret = [0; 5];
+        for (ret_val, midstate_bytes) in ret.iter_mut().zip(midstate.as_ref().bitcoin_as_chunks().0)
+        {
+            *ret_val = u32::from_le_bytes(*midstate_bytes);
+        }
+
+        HashEngine { buffer: [0; BLOCK_SIZE], h: ret, bytes_hashed: midstate.bytes_hashed }
+    }
+
+    /// Returns `true` if the midstate can be extracted from this engine.
+    ///
+    /// The midstate can only be extracted if the number of bytes input into
+    /// the hash engine is a multiple of 64. See caveat on [`Self::midstate`].
+    ///
+    /// Please see docs on [`Midstate`] before using this function.
+    pub const fn can_extract_midstate(&self) -> bool { self.bytes_hashed % 64 == 0 }
+
+    /// Outputs the midstate of the hash engine.
+    ///
+    /// Please see docs on [`Midstate`] before using this function.
+    pub fn midstate(&self) -> Result<Midstate, MidstateError> {
+        if !self.can_extract_midstate() {
+            return Err(MidstateError { invalid_n_bytes_hashed: self.bytes_hashed });
+        }
+        Ok(self.midstate_unchecked())
+    }
+
+    // Does not check that `HashEngine::can_extract_midstate`.
     #[cfg(not(hashes_fuzz))]
-    fn midstate(&self) -> [u8; 20] {
+    fn midstate_unchecked(&self) -> Midstate {
         let mut ret = [0; 20];
         for (val, ret_bytes) in self.h.iter().zip(ret.bitcoin_as_chunks_mut().0) {
             *ret_bytes = val.to_le_bytes();
         }
-        ret
+        Midstate { bytes: ret, bytes_hashed: self.bytes_hashed }
     }
 
+    // Does not check that `HashEngine::can_extract_midstate`.
     #[cfg(hashes_fuzz)]
-    fn midstate(&self) -> [u8; 20] {
+    fn midstate_unchecked(&self) -> Midstate {
         let mut ret = [0; 20];
         ret.copy_from_slice(&self.buffer[..20]);
-        ret
+        Midstate { bytes: ret, bytes_hashed: self.bytes_hashed }
     }
 }
 
-impl Default for HashEngine {
-    fn default() -> Self { Self::new() }
-}
-
 impl crate::HashEngine for HashEngine {
     type Hash = Hash;
     type Bytes = [u8; 20];
@@ -97,3 +133,88 @@ fn n_bytes_hashed(&self) -> u64 { self.bytes_hashed }
     crate::internal_macros::engine_input_impl!();
     fn finalize(self) -> Self::Hash { Hash::from_engine(self) }
 }
+
+/// Unfinalized output of the RIPEMD160 hash function.
+///
+/// The `Midstate` type is obscure and specialized and should not be used unless you are sure of
+/// what you are doing.
+///
+/// It represents "partially hashed data" but does not itself have properties of cryptographic
+/// hashes. For example, when (ab)used as hashes, midstates are vulnerable to trivial
+/// length-extension attacks. They are typically used to optimize the computation of full hashes,
+/// e.g. by hashing a fixed 64-byte prefix once and resuming from the stored midstate.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Midstate {
+    /// Raw bytes of the midstate i.e., the already-hashed contents of the hash engine.
+    bytes: [u8; 20],
+    /// Number of bytes hashed to achieve this midstate.
+    // INVARIANT must always be a multiple of 64.
+    bytes_hashed: u64,
+}
+
+impl Midstate {
+    /// Construct a new [`Midstate`] from the `state` and the `bytes_hashed` to get to that state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_hashed` is not a multiple of 64.
+    pub const fn new(state: [u8; 20], bytes_hashed: u64) -> Self {
+        if bytes_hashed % 64 != 0 {
+            panic!("bytes hashed is not a multiple of 64");
+        }
+
+        Midstate { bytes: state, bytes_hashed }
+    }
+
+    /// Deconstructs the [`Midstate`], returning the underlying byte array and number of bytes hashed.
+    pub const fn as_parts(&self) -> (&[u8; 20], u64) { (&self.bytes, self.bytes_hashed) }
+
+    /// Deconstructs the [`Midstate`], returning the underlying byte array and number of bytes hashed.
+    pub const fn to_parts(self) -> ([u8; 20], u64) { (self.bytes, self.bytes_hashed) }
+}
+
+impl fmt::Debug for Midstate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct Encoder<'a> {
+            bytes: &'a [u8; 20],
+        }
+        impl fmt::Debug for Encoder<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { crate::debug_hex(self.bytes, f) }
+        }
+
+        f.debug_struct("Midstate")
+            .field("bytes", &Encoder { bytes: &self.bytes })
+            .field("length", &self.bytes_hashed)
+            .finish()
+    }
+}
+
+impl convert::AsRef<[u8]> for Midstate {
+    fn as_ref(&self) -> &[u8] { &self.bytes }
+}
+
+/// `Midstate` invariant violated (not a multiple of 64).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MidstateError {
+    /// The invalid number of bytes hashed.
+    invalid_n_bytes_hashed: u64,
+}
+
+impl fmt::Display for MidstateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid number of bytes hashed {} (should have been a multiple of 64)",
+            self.invalid_n_bytes_hashed
+        )
+    }
+}
+
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for MidstateError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for MidstateError {}
+    }
+}
@@ -104,3 +104,28 @@ fn ripemd_serde() {
     assert_tokens(&hash.compact(), &[Token::BorrowedBytes(&HASH_BYTES[..])]);
     assert_tokens(&hash.readable(), &[Token::Str("132072df690933835eb8b6ad0b77e7b6f14acad7")]);
 }
+
+#[test]
+fn midstate_resume() {
+    use crate::{ripemd160, HashEngine};
+
+    let data = [0xab_u8; 150];
+
+    let mut engine = ripemd160::HashEngine::new();
+    engine.input(&data[..63]);
+    assert!(engine.midstate().is_err());
+    engine.input(&data[63..64]);
+    let midstate = engine.midstate().expect("64 bytes hashed");
+    assert_eq!(midstate.as_parts().1, 64);
+
+    let mut resumed = ripemd160::HashEngine::from_midstate(midstate);
+    resumed.input(&data[64..]);
+    assert_eq!(ripemd160::Hash::from_engine(resumed), ripemd160::Hash::hash(&data));
+
+    let (bytes, len) = midstate.to_parts();
+    assert_eq!(ripemd160::Midstate::new(bytes, len), midstate);
+}
+
+#[test]
+#[should_panic]
+fn midstate_new_invalid_length() { let _ = crate::ripemd160::Midstate::new([0; 20], 32); }
@@ -6,6 +6,11 @@
 impl HashEngine {
     // Basic unoptimized algorithm from Wikipedia
     pub(super) fn process_block(&mut self) {
+        crate::internal_macros::trace_span!(
+            "sha1::process_block",
+            bytes_hashed = self.bytes_hashed,
+            backend = "software"
+        );
         debug_assert_eq!(self.buffer.len(), BLOCK_SIZE);
 
         let mut w = [0u32; 80];
@@ -2,8 +2,6 @@
 
 //! SHA1 implementation.
 
-use internals::slice::SliceExt;
-
 #[cfg(bench)]
 mod benches;
 mod crypto;
@@ -12,82 +10,31 @@
 
 use core::cmp;
 
-use crate::{incomplete_block_len, HashEngine as _};
-
 crate::internal_macros::general_hash_type! {
     160,
     false,
     "Output of the SHA1 hash function."
 }
 
-fn from_engine(mut e: HashEngine) -> Hash {
-    // pad buffer with a single 1-bit then all 0s, until there are exactly 8 bytes remaining
-    let n_bytes_hashed = e.bytes_hashed;
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        const EMPTY: [u8; 20] =
+            crate::hex_to_array("da39a3ee5e6b4b0d3255bfef95601890afd80709", false);
+        const ABC: [u8; 20] =
+            crate::hex_to_array("a9993e364706816aba3e25717850c26c9cd0d89d", false);
+        const TWO_BLOCK: [u8; 20] =
+            crate::hex_to_array("84983e441c3bd26ebaae4aa1f95129e5e54670f1", false);
 
-    let zeroes = [0; BLOCK_SIZE - 8];
-    e.input(&[0x80]);
-    if incomplete_block_len(&e) > zeroes.len() {
-        e.input(&zeroes);
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
     }
-    let pad_length = zeroes.len() - incomplete_block_len(&e);
-    e.input(&zeroes[..pad_length]);
-    debug_assert_eq!(incomplete_block_len(&e), zeroes.len());
-
-    e.input(&(8 * n_bytes_hashed).to_be_bytes());
-    debug_assert_eq!(incomplete_block_len(&e), 0);
-
-    Hash(e.midstate())
-}
-
-const BLOCK_SIZE: usize = 64;
-
-/// Engine to compute SHA1 hash function.
-#[derive(Debug, Clone)]
-pub struct HashEngine {
-    buffer: [u8; BLOCK_SIZE],
-    h: [u32; 5],
-    bytes_hashed: u64,
 }
 
-impl HashEngine {
-    /// Constructs a new SHA1 hash engine.
-    pub const fn new() -> Self {
-        Self {
-            h: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
-            bytes_hashed: 0,
-            buffer: [0; BLOCK_SIZE],
-        }
-    }
-
-    #[cfg(not(hashes_fuzz))]
-    pub(crate) fn midstate(&self) -> [u8; 20] {
-        let mut ret = [0; 20];
-        for (val, ret_bytes) in self.h.iter().zip(ret.bitcoin_as_chunks_mut().0) {
-            *ret_bytes = val.to_be_bytes();
-        }
-        ret
-    }
-
-    #[cfg(hashes_fuzz)]
-    pub(crate) fn midstate(&self) -> [u8; 20] {
-        let mut ret = [0; 20];
-        ret.copy_from_slice(&self.buffer[..20]);
-        ret
-    }
-}
-
-impl Default for HashEngine {
-    fn default() -> Self { Self::new() }
-}
-
-impl crate::HashEngine for HashEngine {
-    type Hash = Hash;
-    type Bytes = [u8; 20];
-    const BLOCK_SIZE: usize = 64;
-
-    fn n_bytes_hashed(&self) -> u64 { self.bytes_hashed }
-
-    crate::internal_macros::engine_input_impl!();
-
-    fn finalize(self) -> Self::Hash { Hash::from_engine(self) }
+crate::internal_macros::md_hash_engine! {
+    name: "SHA1",
+    module: sha1,
+    block_size: 64,
+    state: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
+    output: 20,
+    to_bytes: to_be_bytes,
+    length: u64,
 }
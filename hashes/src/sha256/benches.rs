@@ -31,3 +31,13 @@ pub fn sha256_64k(bh: &mut Bencher) {
     });
     bh.bytes = bytes.len() as u64;
 }
+
+#[bench]
+pub fn sha256_compress(bh: &mut Bencher) {
+    let mut state = sha256::Midstate::default();
+    let block = [1u8; 64];
+    bh.iter(|| {
+        state = sha256::Midstate::process_one_block(state, &block);
+    });
+    bh.bytes = block.len() as u64;
+}
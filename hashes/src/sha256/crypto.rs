@@ -1,11 +1,12 @@
 // SPDX-License-Identifier: CC0-1.0
 
-#[cfg(all(feature = "std", target_arch = "x86"))]
+#[cfg(all(feature = "std", not(feature = "backend-sha2"), target_arch = "x86"))]
 use core::arch::x86::*;
-#[cfg(all(feature = "std", target_arch = "x86_64"))]
+#[cfg(all(feature = "std", not(feature = "backend-sha2"), target_arch = "x86_64"))]
 use core::arch::x86_64::*;
 
 use internals::slice::SliceExt;
+
 use super::{HashEngine, Midstate, BLOCK_SIZE};
 
 #[allow(non_snake_case)]
@@ -19,6 +20,10 @@ const fn Sigma1(x: u32) -> u32 { x.rotate_left(26) ^ x.rotate_left(21) ^ x.rotat
 const fn sigma0(x: u32) -> u32 { x.rotate_left(25) ^ x.rotate_left(14) ^ (x >> 3) }
 const fn sigma1(x: u32) -> u32 { x.rotate_left(15) ^ x.rotate_left(13) ^ (x >> 10) }
 
+/// Whether [`software_compress`] uses the compact rounds of the `small-hash` feature.
+pub(super) const SMALL_HASH: bool =
+    cfg!(all(feature = "small-hash", not(feature = "backend-sha2")));
+
 #[cfg(feature = "small-hash")]
 #[macro_use]
 mod small_hash {
@@ -248,373 +253,738 @@ pub(super) const fn compute_midstate_unoptimized(bytes: &[u8], finalize: bool) -
 
 impl HashEngine {
     pub(super) fn process_block(&mut self) {
-        #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+        crate::internal_macros::trace_span!(
+            "sha256::process_block",
+            bytes_hashed = self.bytes_hashed,
+            backend = backend()
+        );
+        compress(&mut self.h, &self.buffer)
+    }
+}
+
+/// Checks that the backend of [`compress`] and [`compress2`] computes known vectors correctly.
+///
+/// The check runs on the first call, later calls only load an atomic flag. Only atomic loads and
+/// stores are used, so this works on targets without compare-and-swap (e.g. `thumbv6m`). The
+/// price is that threads racing on the first call may each run the check.
+///
+/// # Panics
+///
+/// If the active backend produces a wrong digest, e.g. because of a miscompiled SIMD code path or
+/// a faulty CPU.
+#[cfg(feature = "self-test")]
+#[inline]
+fn self_test() {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    const NOT_RUN: u8 = 0;
+    const PASSED: u8 = 1;
+    const FAILED: u8 = 2;
+
+    static STATUS: AtomicU8 = AtomicU8::new(NOT_RUN);
+
+    #[cold]
+    fn run() {
+        // SHA256("abc") and SHA256(""), the single padded blocks and the resulting states.
+        let mut abc = [0; BLOCK_SIZE];
+        abc[..3].copy_from_slice(b"abc");
+        abc[3] = 0x80;
+        abc[BLOCK_SIZE - 1] = 24;
+        let mut empty = [0; BLOCK_SIZE];
+        empty[0] = 0x80;
+        const WANT_ABC: [u32; 8] = [
+            0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+            0xf20015ad,
+        ];
+        const WANT_EMPTY: [u32; 8] = [
+            0xe3b0c442, 0x98fc1c14, 0x9afbf4c8, 0x996fb924, 0x27ae41e4, 0x649b934c, 0xa495991b,
+            0x7852b855,
+        ];
+
+        let mut state = HashEngine::new().h;
+        backend_compress(&mut state, &abc);
+        let mut passed = state == WANT_ABC;
+
+        // Different vectors in the two lanes, so that mixed up lanes are caught too.
+        #[cfg(feature = "alloc")]
         {
-            if std::is_x86_feature_detected!("sse4.1")
-                && std::is_x86_feature_detected!("sha")
-                && std::is_x86_feature_detected!("sse2")
-                && std::is_x86_feature_detected!("ssse3")
-            {
-                return unsafe { self.process_block_simd_x86_intrinsics() };
-            }
+            let (mut state_a, mut state_b) = (HashEngine::new().h, HashEngine::new().h);
+            backend_compress2(&mut state_a, &abc, &mut state_b, &empty);
+            passed &= state_a == WANT_ABC && state_b == WANT_EMPTY;
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let mut state = HashEngine::new().h;
+            backend_compress(&mut state, &empty);
+            passed &= state == WANT_EMPTY;
         }
 
-        // fallback implementation without using any intrinsics
-        self.software_process_block()
+        STATUS.store(if passed { PASSED } else { FAILED }, Ordering::Release);
     }
 
-    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
-    #[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
-    unsafe fn process_block_simd_x86_intrinsics(&mut self) {
-        // Code translated and based on from
-        // https://github.com/noloader/SHA-Intrinsics/blob/4899efc81d1af159c1fd955936c673139f35aea9/sha256-x86.c
+    if STATUS.load(Ordering::Acquire) == PASSED {
+        return;
+    }
+    if STATUS.load(Ordering::Acquire) == NOT_RUN {
+        run();
+    }
+    if STATUS.load(Ordering::Acquire) != PASSED {
+        panic!("sha256 self-test failed, the {} backend is broken", backend());
+    }
+}
 
-        /* sha256-x86.c - Intel SHA extensions using C intrinsics  */
-        /*   Written and place in public domain by Jeffrey Walton  */
-        /*   Based on code from Intel, and by Sean Gulley for      */
-        /*   the miTLS project.                                    */
+/// Runs the SHA256 compression function, checking the backend first with the `self-test`
+/// feature.
+pub(super) fn compress(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    #[cfg(feature = "self-test")]
+    self_test();
+    backend_compress(state, block)
+}
 
-        // Variable names are also kept the same as in the original C code for easier comparison.
-        let (mut state0, mut state1);
-        let (mut msg, mut tmp);
+/// Runs [`compress`] on two independent states, interleaving them on SHA-NI hardware.
+#[cfg(feature = "alloc")]
+pub(super) fn compress2(
+    state_a: &mut [u32; 8],
+    block_a: &[u8; BLOCK_SIZE],
+    state_b: &mut [u32; 8],
+    block_b: &[u8; BLOCK_SIZE],
+) {
+    #[cfg(feature = "self-test")]
+    self_test();
+    backend_compress2(state_a, block_a, state_b, block_b)
+}
 
-        let (mut msg0, mut msg1, mut msg2, mut msg3);
+#[cfg(not(feature = "backend-sha2"))]
+fn backend_compress(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        if has_sha_ni() {
+            return unsafe { compress_simd_x86_intrinsics(state, block) };
+        }
+    }
 
-        let (abef_save, cdgh_save);
+    // fallback implementation without using any intrinsics
+    software_compress(state, block)
+}
 
-        #[allow(non_snake_case)]
-        let MASK: __m128i =
-            _mm_set_epi64x(0x0c0d_0e0f_0809_0a0bu64 as i64, 0x0405_0607_0001_0203u64 as i64);
+#[cfg(feature = "backend-sha2")]
+fn backend_compress(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    let block = sha2::digest::generic_array::GenericArray::from_slice(block);
+    sha2::compress256(state, core::slice::from_ref(block))
+}
 
-        let block_offset = 0;
+#[cfg(feature = "alloc")]
+#[cfg(not(feature = "backend-sha2"))]
+fn backend_compress2(
+    state_a: &mut [u32; 8],
+    block_a: &[u8; BLOCK_SIZE],
+    state_b: &mut [u32; 8],
+    block_b: &[u8; BLOCK_SIZE],
+) {
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        if has_sha_ni() {
+            return unsafe { compress2_simd_x86_intrinsics(state_a, block_a, state_b, block_b) };
+        }
+    }
 
-        // Load initial values
-        // CAST SAFETY: loadu_si128 documentation states that mem_addr does not
-        // need to be aligned on any particular boundary.
-        tmp = _mm_loadu_si128(self.h.as_ptr().add(0) as *const __m128i);
-        state1 = _mm_loadu_si128(self.h.as_ptr().add(4) as *const __m128i);
+    software_compress(state_a, block_a);
+    software_compress(state_b, block_b);
+}
 
-        tmp = _mm_shuffle_epi32(tmp, 0xB1); // CDAB
-        state1 = _mm_shuffle_epi32(state1, 0x1B); // EFGH
-        state0 = _mm_alignr_epi8(tmp, state1, 8); // ABEF
-        state1 = _mm_blend_epi16(state1, tmp, 0xF0); // CDGH
+#[cfg(feature = "alloc")]
+#[cfg(feature = "backend-sha2")]
+fn backend_compress2(
+    state_a: &mut [u32; 8],
+    block_a: &[u8; BLOCK_SIZE],
+    state_b: &mut [u32; 8],
+    block_b: &[u8; BLOCK_SIZE],
+) {
+    backend_compress(state_a, block_a);
+    backend_compress(state_b, block_b);
+}
 
-        // Process a single block
-        {
-            // Save current state
-            abef_save = state0;
-            cdgh_save = state1;
-
-            // Rounds 0-3
-            msg = _mm_loadu_si128(self.buffer.as_ptr().add(block_offset) as *const __m128i);
-            msg0 = _mm_shuffle_epi8(msg, MASK);
-            msg = _mm_add_epi32(
-                msg0,
-                _mm_set_epi64x(0xE9B5DBA5B5C0FBCFu64 as i64, 0x71374491428A2F98u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-
-            // Rounds 4-7
-            msg1 = _mm_loadu_si128(self.buffer.as_ptr().add(block_offset + 16) as *const __m128i);
-            msg1 = _mm_shuffle_epi8(msg1, MASK);
-            msg = _mm_add_epi32(
-                msg1,
-                _mm_set_epi64x(0xAB1C5ED5923F82A4u64 as i64, 0x59F111F13956C25Bu64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg0 = _mm_sha256msg1_epu32(msg0, msg1);
-
-            // Rounds 8-11
-            msg2 = _mm_loadu_si128(self.buffer.as_ptr().add(block_offset + 32) as *const __m128i);
-            msg2 = _mm_shuffle_epi8(msg2, MASK);
-            msg = _mm_add_epi32(
-                msg2,
-                _mm_set_epi64x(0x550C7DC3243185BEu64 as i64, 0x12835B01D807AA98u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg1 = _mm_sha256msg1_epu32(msg1, msg2);
-
-            // Rounds 12-15
-            msg3 = _mm_loadu_si128(self.buffer.as_ptr().add(block_offset + 48) as *const __m128i);
-            msg3 = _mm_shuffle_epi8(msg3, MASK);
-            msg = _mm_add_epi32(
-                msg3,
-                _mm_set_epi64x(0xC19BF1749BDC06A7u64 as i64, 0x80DEB1FE72BE5D74u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg3, msg2, 4);
-            msg0 = _mm_add_epi32(msg0, tmp);
-            msg0 = _mm_sha256msg2_epu32(msg0, msg3);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg2 = _mm_sha256msg1_epu32(msg2, msg3);
-
-            // Rounds 16-19
-            msg = _mm_add_epi32(
-                msg0,
-                _mm_set_epi64x(0x240CA1CC0FC19DC6u64 as i64, 0xEFBE4786E49B69C1u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg0, msg3, 4);
-            msg1 = _mm_add_epi32(msg1, tmp);
-            msg1 = _mm_sha256msg2_epu32(msg1, msg0);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg3 = _mm_sha256msg1_epu32(msg3, msg0);
-
-            // Rounds 20-23
-            msg = _mm_add_epi32(
-                msg1,
-                _mm_set_epi64x(0x76F988DA5CB0A9DCu64 as i64, 0x4A7484AA2DE92C6Fu64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg1, msg0, 4);
-            msg2 = _mm_add_epi32(msg2, tmp);
-            msg2 = _mm_sha256msg2_epu32(msg2, msg1);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg0 = _mm_sha256msg1_epu32(msg0, msg1);
-
-            // Rounds 24-27
-            msg = _mm_add_epi32(
-                msg2,
-                _mm_set_epi64x(0xBF597FC7B00327C8u64 as i64, 0xA831C66D983E5152u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg2, msg1, 4);
-            msg3 = _mm_add_epi32(msg3, tmp);
-            msg3 = _mm_sha256msg2_epu32(msg3, msg2);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg1 = _mm_sha256msg1_epu32(msg1, msg2);
-
-            // Rounds 28-31
-            msg = _mm_add_epi32(
-                msg3,
-                _mm_set_epi64x(0x1429296706CA6351u64 as i64, 0xD5A79147C6E00BF3u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg3, msg2, 4);
-            msg0 = _mm_add_epi32(msg0, tmp);
-            msg0 = _mm_sha256msg2_epu32(msg0, msg3);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg2 = _mm_sha256msg1_epu32(msg2, msg3);
-
-            // Rounds 32-35
-            msg = _mm_add_epi32(
-                msg0,
-                _mm_set_epi64x(0x53380D134D2C6DFCu64 as i64, 0x2E1B213827B70A85u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg0, msg3, 4);
-            msg1 = _mm_add_epi32(msg1, tmp);
-            msg1 = _mm_sha256msg2_epu32(msg1, msg0);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg3 = _mm_sha256msg1_epu32(msg3, msg0);
-
-            // Rounds 36-39
-            msg = _mm_add_epi32(
-                msg1,
-                _mm_set_epi64x(0x92722C8581C2C92Eu64 as i64, 0x766A0ABB650A7354u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg1, msg0, 4);
-            msg2 = _mm_add_epi32(msg2, tmp);
-            msg2 = _mm_sha256msg2_epu32(msg2, msg1);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg0 = _mm_sha256msg1_epu32(msg0, msg1);
-
-            // Rounds 40-43
-            msg = _mm_add_epi32(
-                msg2,
-                _mm_set_epi64x(0xC76C51A3C24B8B70u64 as i64, 0xA81A664BA2BFE8A1u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg2, msg1, 4);
-            msg3 = _mm_add_epi32(msg3, tmp);
-            msg3 = _mm_sha256msg2_epu32(msg3, msg2);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg1 = _mm_sha256msg1_epu32(msg1, msg2);
-
-            // Rounds 44-47
-            msg = _mm_add_epi32(
-                msg3,
-                _mm_set_epi64x(0x106AA070F40E3585u64 as i64, 0xD6990624D192E819u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg3, msg2, 4);
-            msg0 = _mm_add_epi32(msg0, tmp);
-            msg0 = _mm_sha256msg2_epu32(msg0, msg3);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg2 = _mm_sha256msg1_epu32(msg2, msg3);
-
-            // Rounds 48-51
-            msg = _mm_add_epi32(
-                msg0,
-                _mm_set_epi64x(0x34B0BCB52748774Cu64 as i64, 0x1E376C0819A4C116u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg0, msg3, 4);
-            msg1 = _mm_add_epi32(msg1, tmp);
-            msg1 = _mm_sha256msg2_epu32(msg1, msg0);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-            msg3 = _mm_sha256msg1_epu32(msg3, msg0);
-
-            // Rounds 52-55
-            msg = _mm_add_epi32(
-                msg1,
-                _mm_set_epi64x(0x682E6FF35B9CCA4Fu64 as i64, 0x4ED8AA4A391C0CB3u64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg1, msg0, 4);
-            msg2 = _mm_add_epi32(msg2, tmp);
-            msg2 = _mm_sha256msg2_epu32(msg2, msg1);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-
-            // Rounds 56-59
-            msg = _mm_add_epi32(
-                msg2,
-                _mm_set_epi64x(0x8CC7020884C87814u64 as i64, 0x78A5636F748F82EEu64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            tmp = _mm_alignr_epi8(msg2, msg1, 4);
-            msg3 = _mm_add_epi32(msg3, tmp);
-            msg3 = _mm_sha256msg2_epu32(msg3, msg2);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-
-            // Rounds 60-63
-            msg = _mm_add_epi32(
-                msg3,
-                _mm_set_epi64x(0xC67178F2BEF9A3F7u64 as i64, 0xA4506CEB90BEFFFAu64 as i64),
-            );
-            state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
-            msg = _mm_shuffle_epi32(msg, 0x0E);
-            state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
-
-            // Combine state
-            state0 = _mm_add_epi32(state0, abef_save);
-            state1 = _mm_add_epi32(state1, cdgh_save);
+/// Returns the name of the implementation used by [`compress`].
+#[cfg(all(any(feature = "tracing", feature = "self-test"), not(feature = "backend-sha2")))]
+fn backend() -> &'static str {
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        if has_sha_ni() {
+            return "sha-ni";
         }
+    }
+    "software"
+}
+
+/// Returns the name of the implementation used by [`compress`].
+#[cfg(all(any(feature = "tracing", feature = "self-test"), feature = "backend-sha2"))]
+fn backend() -> &'static str { "sha2" }
+
+#[cfg(all(
+    feature = "std",
+    not(feature = "backend-sha2"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+fn has_sha_ni() -> bool {
+    #[cfg(test)]
+    if let Some(sha_ni) = FORCE_SHA_NI.with(|force| force.get()) {
+        return sha_ni;
+    }
+    sha_ni_detected()
+}
 
-        tmp = _mm_shuffle_epi32(state0, 0x1B); // FEBA
-        state1 = _mm_shuffle_epi32(state1, 0xB1); // DCHG
-        state0 = _mm_blend_epi16(tmp, state1, 0xF0); // DCBA
-        state1 = _mm_alignr_epi8(state1, tmp, 8); // ABEF
+#[cfg(all(
+    feature = "std",
+    not(feature = "backend-sha2"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+fn sha_ni_detected() -> bool {
+    std::is_x86_feature_detected!("sse4.1")
+        && std::is_x86_feature_detected!("sha")
+        && std::is_x86_feature_detected!("sse2")
+        && std::is_x86_feature_detected!("ssse3")
+}
 
-        // Save state
-        // CAST SAFETY: storeu_si128 documentation states that mem_addr does not
-        // need to be aligned on any particular boundary.
-        _mm_storeu_si128(self.h.as_mut_ptr().add(0) as *mut __m128i, state0);
-        _mm_storeu_si128(self.h.as_mut_ptr().add(4) as *mut __m128i, state1);
+#[cfg(test)]
+#[cfg(all(
+    feature = "std",
+    not(feature = "backend-sha2"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+std::thread_local! {
+    /// Overrides the runtime detection of [`has_sha_ni`] on this thread.
+    static FORCE_SHA_NI: core::cell::Cell<Option<bool>> = const { core::cell::Cell::new(None) };
+}
+
+/// Runs `f` with [`compress`] forced to use SHA-NI (`sha_ni == true`) or the software
+/// implementation on the current thread.
+///
+/// Returns `None` without running `f` if SHA-NI is requested but not supported by the CPU, so that
+/// tests can tell the SIMD path was skipped rather than silently testing the software one.
+#[cfg(test)]
+#[cfg(all(
+    feature = "std",
+    not(feature = "backend-sha2"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+fn with_sha_ni<R>(sha_ni: bool, f: impl FnOnce() -> R) -> Option<R> {
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) { FORCE_SHA_NI.with(|force| force.set(None)) }
     }
 
-    // Algorithm copied from libsecp256k1
-    fn software_process_block(&mut self) {
-        debug_assert_eq!(self.buffer.len(), BLOCK_SIZE);
+    if sha_ni && !sha_ni_detected() {
+        return None;
+    }
+    FORCE_SHA_NI.with(|force| force.set(Some(sha_ni)));
+    let _reset = Reset;
+    Some(f())
+}
 
-        let mut w = [0u32; 16];
-        for (w_val, buff_bytes) in w.iter_mut().zip(self.buffer.bitcoin_as_chunks().0) {
-            *w_val = u32::from_be_bytes(*buff_bytes);
+#[cfg(all(
+    feature = "std",
+    not(feature = "backend-sha2"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+unsafe fn compress_simd_x86_intrinsics(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    // Code translated and based on from
+    // https://github.com/noloader/SHA-Intrinsics/blob/4899efc81d1af159c1fd955936c673139f35aea9/sha256-x86.c
+
+    /* sha256-x86.c - Intel SHA extensions using C intrinsics  */
+    /*   Written and place in public domain by Jeffrey Walton  */
+    /*   Based on code from Intel, and by Sean Gulley for      */
+    /*   the miTLS project.                                    */
+
+    // Variable names are also kept the same as in the original C code for easier comparison.
+    let (mut state0, mut state1);
+    let (mut msg, mut tmp);
+
+    let (mut msg0, mut msg1, mut msg2, mut msg3);
+
+    let (abef_save, cdgh_save);
+
+    #[allow(non_snake_case)]
+    let MASK: __m128i =
+        _mm_set_epi64x(0x0c0d_0e0f_0809_0a0bu64 as i64, 0x0405_0607_0001_0203u64 as i64);
+
+    let block_offset = 0;
+
+    // Load initial values
+    // CAST SAFETY: loadu_si128 documentation states that mem_addr does not
+    // need to be aligned on any particular boundary.
+    tmp = _mm_loadu_si128(state.as_ptr().add(0) as *const __m128i);
+    state1 = _mm_loadu_si128(state.as_ptr().add(4) as *const __m128i);
+
+    tmp = _mm_shuffle_epi32(tmp, 0xB1); // CDAB
+    state1 = _mm_shuffle_epi32(state1, 0x1B); // EFGH
+    state0 = _mm_alignr_epi8(tmp, state1, 8); // ABEF
+    state1 = _mm_blend_epi16(state1, tmp, 0xF0); // CDGH
+
+    // Process a single block
+    {
+        // Save current state
+        abef_save = state0;
+        cdgh_save = state1;
+
+        // Rounds 0-3
+        msg = _mm_loadu_si128(block.as_ptr().add(block_offset) as *const __m128i);
+        msg0 = _mm_shuffle_epi8(msg, MASK);
+        msg = _mm_add_epi32(
+            msg0,
+            _mm_set_epi64x(0xE9B5DBA5B5C0FBCFu64 as i64, 0x71374491428A2F98u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+
+        // Rounds 4-7
+        msg1 = _mm_loadu_si128(block.as_ptr().add(block_offset + 16) as *const __m128i);
+        msg1 = _mm_shuffle_epi8(msg1, MASK);
+        msg = _mm_add_epi32(
+            msg1,
+            _mm_set_epi64x(0xAB1C5ED5923F82A4u64 as i64, 0x59F111F13956C25Bu64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg0 = _mm_sha256msg1_epu32(msg0, msg1);
+
+        // Rounds 8-11
+        msg2 = _mm_loadu_si128(block.as_ptr().add(block_offset + 32) as *const __m128i);
+        msg2 = _mm_shuffle_epi8(msg2, MASK);
+        msg = _mm_add_epi32(
+            msg2,
+            _mm_set_epi64x(0x550C7DC3243185BEu64 as i64, 0x12835B01D807AA98u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg1 = _mm_sha256msg1_epu32(msg1, msg2);
+
+        // Rounds 12-15
+        msg3 = _mm_loadu_si128(block.as_ptr().add(block_offset + 48) as *const __m128i);
+        msg3 = _mm_shuffle_epi8(msg3, MASK);
+        msg = _mm_add_epi32(
+            msg3,
+            _mm_set_epi64x(0xC19BF1749BDC06A7u64 as i64, 0x80DEB1FE72BE5D74u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg3, msg2, 4);
+        msg0 = _mm_add_epi32(msg0, tmp);
+        msg0 = _mm_sha256msg2_epu32(msg0, msg3);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg2 = _mm_sha256msg1_epu32(msg2, msg3);
+
+        // Rounds 16-19
+        msg = _mm_add_epi32(
+            msg0,
+            _mm_set_epi64x(0x240CA1CC0FC19DC6u64 as i64, 0xEFBE4786E49B69C1u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg0, msg3, 4);
+        msg1 = _mm_add_epi32(msg1, tmp);
+        msg1 = _mm_sha256msg2_epu32(msg1, msg0);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg3 = _mm_sha256msg1_epu32(msg3, msg0);
+
+        // Rounds 20-23
+        msg = _mm_add_epi32(
+            msg1,
+            _mm_set_epi64x(0x76F988DA5CB0A9DCu64 as i64, 0x4A7484AA2DE92C6Fu64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg1, msg0, 4);
+        msg2 = _mm_add_epi32(msg2, tmp);
+        msg2 = _mm_sha256msg2_epu32(msg2, msg1);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg0 = _mm_sha256msg1_epu32(msg0, msg1);
+
+        // Rounds 24-27
+        msg = _mm_add_epi32(
+            msg2,
+            _mm_set_epi64x(0xBF597FC7B00327C8u64 as i64, 0xA831C66D983E5152u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg2, msg1, 4);
+        msg3 = _mm_add_epi32(msg3, tmp);
+        msg3 = _mm_sha256msg2_epu32(msg3, msg2);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg1 = _mm_sha256msg1_epu32(msg1, msg2);
+
+        // Rounds 28-31
+        msg = _mm_add_epi32(
+            msg3,
+            _mm_set_epi64x(0x1429296706CA6351u64 as i64, 0xD5A79147C6E00BF3u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg3, msg2, 4);
+        msg0 = _mm_add_epi32(msg0, tmp);
+        msg0 = _mm_sha256msg2_epu32(msg0, msg3);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg2 = _mm_sha256msg1_epu32(msg2, msg3);
+
+        // Rounds 32-35
+        msg = _mm_add_epi32(
+            msg0,
+            _mm_set_epi64x(0x53380D134D2C6DFCu64 as i64, 0x2E1B213827B70A85u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg0, msg3, 4);
+        msg1 = _mm_add_epi32(msg1, tmp);
+        msg1 = _mm_sha256msg2_epu32(msg1, msg0);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg3 = _mm_sha256msg1_epu32(msg3, msg0);
+
+        // Rounds 36-39
+        msg = _mm_add_epi32(
+            msg1,
+            _mm_set_epi64x(0x92722C8581C2C92Eu64 as i64, 0x766A0ABB650A7354u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg1, msg0, 4);
+        msg2 = _mm_add_epi32(msg2, tmp);
+        msg2 = _mm_sha256msg2_epu32(msg2, msg1);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg0 = _mm_sha256msg1_epu32(msg0, msg1);
+
+        // Rounds 40-43
+        msg = _mm_add_epi32(
+            msg2,
+            _mm_set_epi64x(0xC76C51A3C24B8B70u64 as i64, 0xA81A664BA2BFE8A1u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg2, msg1, 4);
+        msg3 = _mm_add_epi32(msg3, tmp);
+        msg3 = _mm_sha256msg2_epu32(msg3, msg2);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg1 = _mm_sha256msg1_epu32(msg1, msg2);
+
+        // Rounds 44-47
+        msg = _mm_add_epi32(
+            msg3,
+            _mm_set_epi64x(0x106AA070F40E3585u64 as i64, 0xD6990624D192E819u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg3, msg2, 4);
+        msg0 = _mm_add_epi32(msg0, tmp);
+        msg0 = _mm_sha256msg2_epu32(msg0, msg3);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg2 = _mm_sha256msg1_epu32(msg2, msg3);
+
+        // Rounds 48-51
+        msg = _mm_add_epi32(
+            msg0,
+            _mm_set_epi64x(0x34B0BCB52748774Cu64 as i64, 0x1E376C0819A4C116u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg0, msg3, 4);
+        msg1 = _mm_add_epi32(msg1, tmp);
+        msg1 = _mm_sha256msg2_epu32(msg1, msg0);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+        msg3 = _mm_sha256msg1_epu32(msg3, msg0);
+
+        // Rounds 52-55
+        msg = _mm_add_epi32(
+            msg1,
+            _mm_set_epi64x(0x682E6FF35B9CCA4Fu64 as i64, 0x4ED8AA4A391C0CB3u64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg1, msg0, 4);
+        msg2 = _mm_add_epi32(msg2, tmp);
+        msg2 = _mm_sha256msg2_epu32(msg2, msg1);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+
+        // Rounds 56-59
+        msg = _mm_add_epi32(
+            msg2,
+            _mm_set_epi64x(0x8CC7020884C87814u64 as i64, 0x78A5636F748F82EEu64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        tmp = _mm_alignr_epi8(msg2, msg1, 4);
+        msg3 = _mm_add_epi32(msg3, tmp);
+        msg3 = _mm_sha256msg2_epu32(msg3, msg2);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+
+        // Rounds 60-63
+        msg = _mm_add_epi32(
+            msg3,
+            _mm_set_epi64x(0xC67178F2BEF9A3F7u64 as i64, 0xA4506CEB90BEFFFAu64 as i64),
+        );
+        state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+        msg = _mm_shuffle_epi32(msg, 0x0E);
+        state0 = _mm_sha256rnds2_epu32(state0, state1, msg);
+
+        // Combine state
+        state0 = _mm_add_epi32(state0, abef_save);
+        state1 = _mm_add_epi32(state1, cdgh_save);
+    }
+
+    tmp = _mm_shuffle_epi32(state0, 0x1B); // FEBA
+    state1 = _mm_shuffle_epi32(state1, 0xB1); // DCHG
+    state0 = _mm_blend_epi16(tmp, state1, 0xF0); // DCBA
+    state1 = _mm_alignr_epi8(state1, tmp, 8); // ABEF
+
+    // Save state
+    // CAST SAFETY: storeu_si128 documentation states that mem_addr does not
+    // need to be aligned on any particular boundary.
+    _mm_storeu_si128(state.as_mut_ptr().add(0) as *mut __m128i, state0);
+    _mm_storeu_si128(state.as_mut_ptr().add(4) as *mut __m128i, state1);
+}
+
+/// Two lane version of [`compress_simd_x86_intrinsics`].
+///
+/// The message schedules and rounds of both blocks are independent, issuing them side by side lets
+/// the CPU overlap the latency of the `sha256rnds2` instructions of one lane with the other.
+#[cfg(all(
+    feature = "std",
+    not(feature = "backend-sha2"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+unsafe fn compress2_simd_x86_intrinsics(
+    state_a: &mut [u32; 8],
+    block_a: &[u8; BLOCK_SIZE],
+    state_b: &mut [u32; 8],
+    block_b: &[u8; BLOCK_SIZE],
+) {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    #[allow(non_snake_case)]
+    let MASK: __m128i =
+        _mm_set_epi64x(0x0c0d_0e0f_0809_0a0bu64 as i64, 0x0405_0607_0001_0203u64 as i64);
+
+    let blocks = [block_a, block_b];
+    let mut state0 = [_mm_setzero_si128(); 2];
+    let mut state1 = [_mm_setzero_si128(); 2];
+    let mut msgs = [[_mm_setzero_si128(); 4]; 2];
+
+    // Load initial values, see `compress_simd_x86_intrinsics` for the shuffles.
+    for (lane, state) in [&*state_a, &*state_b].into_iter().enumerate() {
+        let tmp = _mm_shuffle_epi32(_mm_loadu_si128(state.as_ptr() as *const __m128i), 0xB1);
+        let efgh =
+            _mm_shuffle_epi32(_mm_loadu_si128(state.as_ptr().add(4) as *const __m128i), 0x1B);
+        state0[lane] = _mm_alignr_epi8(tmp, efgh, 8);
+        state1[lane] = _mm_blend_epi16(efgh, tmp, 0xF0);
+    }
+    let (abef_save, cdgh_save) = (state0, state1);
+
+    // Same rounds as the single lane version. A macro rather than a loop so that all indices are
+    // constant and the whole state stays in registers.
+    macro_rules! rounds {
+        ($i:expr, $lane:expr) => {{
+            let (i, lane) = ($i, $lane);
+            let msgs = &mut msgs[lane];
+            if i < 4 {
+                let msg = _mm_loadu_si128(blocks[lane].as_ptr().add(16 * i) as *const __m128i);
+                msgs[i] = _mm_shuffle_epi8(msg, MASK);
+            }
+
+            let k = _mm_loadu_si128(K.as_ptr().add(4 * i) as *const __m128i);
+            let msg = _mm_add_epi32(msgs[i % 4], k);
+            state1[lane] = _mm_sha256rnds2_epu32(state1[lane], state0[lane], msg);
+            if i >= 3 && i <= 14 {
+                let tmp = _mm_alignr_epi8(msgs[i % 4], msgs[(i + 3) % 4], 4);
+                let next = _mm_add_epi32(msgs[(i + 1) % 4], tmp);
+                msgs[(i + 1) % 4] = _mm_sha256msg2_epu32(next, msgs[i % 4]);
+            }
+            let msg = _mm_shuffle_epi32(msg, 0x0E);
+            state0[lane] = _mm_sha256rnds2_epu32(state0[lane], state1[lane], msg);
+            if i >= 1 && i <= 12 {
+                msgs[(i + 3) % 4] = _mm_sha256msg1_epu32(msgs[(i + 3) % 4], msgs[i % 4]);
+            }
+        }};
+    }
+    macro_rules! interleaved_rounds {
+        ($($i:expr),*) => { $( rounds!($i, 0); rounds!($i, 1); )* };
+    }
+    interleaved_rounds!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+
+    for (lane, state) in [state_a, state_b].into_iter().enumerate() {
+        let abef = _mm_add_epi32(state0[lane], abef_save[lane]);
+        let cdgh = _mm_add_epi32(state1[lane], cdgh_save[lane]);
+
+        let feba = _mm_shuffle_epi32(abef, 0x1B);
+        let dchg = _mm_shuffle_epi32(cdgh, 0xB1);
+        _mm_storeu_si128(state.as_mut_ptr() as *mut __m128i, _mm_blend_epi16(feba, dchg, 0xF0));
+        _mm_storeu_si128(state.as_mut_ptr().add(4) as *mut __m128i, _mm_alignr_epi8(dchg, feba, 8));
+    }
+}
+
+// Algorithm copied from libsecp256k1
+pub(super) fn software_compress(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    let mut w = [0u32; 16];
+    for (w_val, buff_bytes) in w.iter_mut().zip(block.bitcoin_as_chunks().0) {
+        *w_val = u32::from_be_bytes(*buff_bytes);
+    }
+
+    let mut a = state[0];
+    let mut b = state[1];
+    let mut c = state[2];
+    let mut d = state[3];
+    let mut e = state[4];
+    let mut f = state[5];
+    let mut g = state[6];
+    let mut h = state[7];
+
+    round!(a, b, c, d, e, f, g, h, 0x428a2f98, w[0]);
+    round!(h, a, b, c, d, e, f, g, 0x71374491, w[1]);
+    round!(g, h, a, b, c, d, e, f, 0xb5c0fbcf, w[2]);
+    round!(f, g, h, a, b, c, d, e, 0xe9b5dba5, w[3]);
+    round!(e, f, g, h, a, b, c, d, 0x3956c25b, w[4]);
+    round!(d, e, f, g, h, a, b, c, 0x59f111f1, w[5]);
+    round!(c, d, e, f, g, h, a, b, 0x923f82a4, w[6]);
+    round!(b, c, d, e, f, g, h, a, 0xab1c5ed5, w[7]);
+    round!(a, b, c, d, e, f, g, h, 0xd807aa98, w[8]);
+    round!(h, a, b, c, d, e, f, g, 0x12835b01, w[9]);
+    round!(g, h, a, b, c, d, e, f, 0x243185be, w[10]);
+    round!(f, g, h, a, b, c, d, e, 0x550c7dc3, w[11]);
+    round!(e, f, g, h, a, b, c, d, 0x72be5d74, w[12]);
+    round!(d, e, f, g, h, a, b, c, 0x80deb1fe, w[13]);
+    round!(c, d, e, f, g, h, a, b, 0x9bdc06a7, w[14]);
+    round!(b, c, d, e, f, g, h, a, 0xc19bf174, w[15]);
+
+    round!(a, b, c, d, e, f, g, h, 0xe49b69c1, w[0], w[14], w[9], w[1]);
+    round!(h, a, b, c, d, e, f, g, 0xefbe4786, w[1], w[15], w[10], w[2]);
+    round!(g, h, a, b, c, d, e, f, 0x0fc19dc6, w[2], w[0], w[11], w[3]);
+    round!(f, g, h, a, b, c, d, e, 0x240ca1cc, w[3], w[1], w[12], w[4]);
+    round!(e, f, g, h, a, b, c, d, 0x2de92c6f, w[4], w[2], w[13], w[5]);
+    round!(d, e, f, g, h, a, b, c, 0x4a7484aa, w[5], w[3], w[14], w[6]);
+    round!(c, d, e, f, g, h, a, b, 0x5cb0a9dc, w[6], w[4], w[15], w[7]);
+    round!(b, c, d, e, f, g, h, a, 0x76f988da, w[7], w[5], w[0], w[8]);
+    round!(a, b, c, d, e, f, g, h, 0x983e5152, w[8], w[6], w[1], w[9]);
+    round!(h, a, b, c, d, e, f, g, 0xa831c66d, w[9], w[7], w[2], w[10]);
+    round!(g, h, a, b, c, d, e, f, 0xb00327c8, w[10], w[8], w[3], w[11]);
+    round!(f, g, h, a, b, c, d, e, 0xbf597fc7, w[11], w[9], w[4], w[12]);
+    round!(e, f, g, h, a, b, c, d, 0xc6e00bf3, w[12], w[10], w[5], w[13]);
+    round!(d, e, f, g, h, a, b, c, 0xd5a79147, w[13], w[11], w[6], w[14]);
+    round!(c, d, e, f, g, h, a, b, 0x06ca6351, w[14], w[12], w[7], w[15]);
+    round!(b, c, d, e, f, g, h, a, 0x14292967, w[15], w[13], w[8], w[0]);
+
+    round!(a, b, c, d, e, f, g, h, 0x27b70a85, w[0], w[14], w[9], w[1]);
+    round!(h, a, b, c, d, e, f, g, 0x2e1b2138, w[1], w[15], w[10], w[2]);
+    round!(g, h, a, b, c, d, e, f, 0x4d2c6dfc, w[2], w[0], w[11], w[3]);
+    round!(f, g, h, a, b, c, d, e, 0x53380d13, w[3], w[1], w[12], w[4]);
+    round!(e, f, g, h, a, b, c, d, 0x650a7354, w[4], w[2], w[13], w[5]);
+    round!(d, e, f, g, h, a, b, c, 0x766a0abb, w[5], w[3], w[14], w[6]);
+    round!(c, d, e, f, g, h, a, b, 0x81c2c92e, w[6], w[4], w[15], w[7]);
+    round!(b, c, d, e, f, g, h, a, 0x92722c85, w[7], w[5], w[0], w[8]);
+    round!(a, b, c, d, e, f, g, h, 0xa2bfe8a1, w[8], w[6], w[1], w[9]);
+    round!(h, a, b, c, d, e, f, g, 0xa81a664b, w[9], w[7], w[2], w[10]);
+    round!(g, h, a, b, c, d, e, f, 0xc24b8b70, w[10], w[8], w[3], w[11]);
+    round!(f, g, h, a, b, c, d, e, 0xc76c51a3, w[11], w[9], w[4], w[12]);
+    round!(e, f, g, h, a, b, c, d, 0xd192e819, w[12], w[10], w[5], w[13]);
+    round!(d, e, f, g, h, a, b, c, 0xd6990624, w[13], w[11], w[6], w[14]);
+    round!(c, d, e, f, g, h, a, b, 0xf40e3585, w[14], w[12], w[7], w[15]);
+    round!(b, c, d, e, f, g, h, a, 0x106aa070, w[15], w[13], w[8], w[0]);
+
+    round!(a, b, c, d, e, f, g, h, 0x19a4c116, w[0], w[14], w[9], w[1]);
+    round!(h, a, b, c, d, e, f, g, 0x1e376c08, w[1], w[15], w[10], w[2]);
+    round!(g, h, a, b, c, d, e, f, 0x2748774c, w[2], w[0], w[11], w[3]);
+    round!(f, g, h, a, b, c, d, e, 0x34b0bcb5, w[3], w[1], w[12], w[4]);
+    round!(e, f, g, h, a, b, c, d, 0x391c0cb3, w[4], w[2], w[13], w[5]);
+    round!(d, e, f, g, h, a, b, c, 0x4ed8aa4a, w[5], w[3], w[14], w[6]);
+    round!(c, d, e, f, g, h, a, b, 0x5b9cca4f, w[6], w[4], w[15], w[7]);
+    round!(b, c, d, e, f, g, h, a, 0x682e6ff3, w[7], w[5], w[0], w[8]);
+    round!(a, b, c, d, e, f, g, h, 0x748f82ee, w[8], w[6], w[1], w[9]);
+    round!(h, a, b, c, d, e, f, g, 0x78a5636f, w[9], w[7], w[2], w[10]);
+    round!(g, h, a, b, c, d, e, f, 0x84c87814, w[10], w[8], w[3], w[11]);
+    round!(f, g, h, a, b, c, d, e, 0x8cc70208, w[11], w[9], w[4], w[12]);
+    round!(e, f, g, h, a, b, c, d, 0x90befffa, w[12], w[10], w[5], w[13]);
+    round!(d, e, f, g, h, a, b, c, 0xa4506ceb, w[13], w[11], w[6], w[14]);
+    round!(c, d, e, f, g, h, a, b, 0xbef9a3f7, w[14], w[12], w[7], w[15]);
+    round!(b, c, d, e, f, g, h, a, 0xc67178f2, w[15], w[13], w[8], w[0]);
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+    state[4] = state[4].wrapping_add(e);
+    state[5] = state[5].wrapping_add(f);
+    state[6] = state[6].wrapping_add(g);
+    state[7] = state[7].wrapping_add(h);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(all(
+        feature = "std",
+        not(feature = "backend-sha2"),
+        any(target_arch = "x86", target_arch = "x86_64")
+    ))]
+    fn sha_ni_matches_software() {
+        use core::array;
+
+        use super::super::{finalize_pair, Hash, HashEngine};
+        use super::with_sha_ni;
+
+        let data: [u8; 1000] = array::from_fn(|i| (i * 7 % 251) as u8);
+        let hash_prefixes = || -> [Hash; 28] { array::from_fn(|i| Hash::hash(&data[..i * 37])) };
+        let hash_pair = || {
+            let engine = HashEngine::new();
+            finalize_pair(engine.clone(), &data[..300], engine, &data[100..])
+        };
+
+        let software = with_sha_ni(false, hash_prefixes).unwrap();
+        let software_pair = with_sha_ni(false, hash_pair).unwrap();
+        assert_eq!(software[1], Hash::hash_unoptimized(&data[..37]));
+        assert_eq!(software_pair, (Hash::hash(&data[..300]), Hash::hash(&data[100..])));
+
+        match with_sha_ni(true, || (hash_prefixes(), hash_pair())) {
+            Some((sha_ni, sha_ni_pair)) => {
+                assert_eq!(sha_ni, software);
+                assert_eq!(sha_ni_pair, software_pair);
+            }
+            None => std::eprintln!("skipping SHA-NI test: the CPU does not support SHA-NI"),
         }
+    }
 
-        let mut a = self.h[0];
-        let mut b = self.h[1];
-        let mut c = self.h[2];
-        let mut d = self.h[3];
-        let mut e = self.h[4];
-        let mut f = self.h[5];
-        let mut g = self.h[6];
-        let mut h = self.h[7];
-
-        round!(a, b, c, d, e, f, g, h, 0x428a2f98, w[0]);
-        round!(h, a, b, c, d, e, f, g, 0x71374491, w[1]);
-        round!(g, h, a, b, c, d, e, f, 0xb5c0fbcf, w[2]);
-        round!(f, g, h, a, b, c, d, e, 0xe9b5dba5, w[3]);
-        round!(e, f, g, h, a, b, c, d, 0x3956c25b, w[4]);
-        round!(d, e, f, g, h, a, b, c, 0x59f111f1, w[5]);
-        round!(c, d, e, f, g, h, a, b, 0x923f82a4, w[6]);
-        round!(b, c, d, e, f, g, h, a, 0xab1c5ed5, w[7]);
-        round!(a, b, c, d, e, f, g, h, 0xd807aa98, w[8]);
-        round!(h, a, b, c, d, e, f, g, 0x12835b01, w[9]);
-        round!(g, h, a, b, c, d, e, f, 0x243185be, w[10]);
-        round!(f, g, h, a, b, c, d, e, 0x550c7dc3, w[11]);
-        round!(e, f, g, h, a, b, c, d, 0x72be5d74, w[12]);
-        round!(d, e, f, g, h, a, b, c, 0x80deb1fe, w[13]);
-        round!(c, d, e, f, g, h, a, b, 0x9bdc06a7, w[14]);
-        round!(b, c, d, e, f, g, h, a, 0xc19bf174, w[15]);
-
-        round!(a, b, c, d, e, f, g, h, 0xe49b69c1, w[0], w[14], w[9], w[1]);
-        round!(h, a, b, c, d, e, f, g, 0xefbe4786, w[1], w[15], w[10], w[2]);
-        round!(g, h, a, b, c, d, e, f, 0x0fc19dc6, w[2], w[0], w[11], w[3]);
-        round!(f, g, h, a, b, c, d, e, 0x240ca1cc, w[3], w[1], w[12], w[4]);
-        round!(e, f, g, h, a, b, c, d, 0x2de92c6f, w[4], w[2], w[13], w[5]);
-        round!(d, e, f, g, h, a, b, c, 0x4a7484aa, w[5], w[3], w[14], w[6]);
-        round!(c, d, e, f, g, h, a, b, 0x5cb0a9dc, w[6], w[4], w[15], w[7]);
-        round!(b, c, d, e, f, g, h, a, 0x76f988da, w[7], w[5], w[0], w[8]);
-        round!(a, b, c, d, e, f, g, h, 0x983e5152, w[8], w[6], w[1], w[9]);
-        round!(h, a, b, c, d, e, f, g, 0xa831c66d, w[9], w[7], w[2], w[10]);
-        round!(g, h, a, b, c, d, e, f, 0xb00327c8, w[10], w[8], w[3], w[11]);
-        round!(f, g, h, a, b, c, d, e, 0xbf597fc7, w[11], w[9], w[4], w[12]);
-        round!(e, f, g, h, a, b, c, d, 0xc6e00bf3, w[12], w[10], w[5], w[13]);
-        round!(d, e, f, g, h, a, b, c, 0xd5a79147, w[13], w[11], w[6], w[14]);
-        round!(c, d, e, f, g, h, a, b, 0x06ca6351, w[14], w[12], w[7], w[15]);
-        round!(b, c, d, e, f, g, h, a, 0x14292967, w[15], w[13], w[8], w[0]);
-
-        round!(a, b, c, d, e, f, g, h, 0x27b70a85, w[0], w[14], w[9], w[1]);
-        round!(h, a, b, c, d, e, f, g, 0x2e1b2138, w[1], w[15], w[10], w[2]);
-        round!(g, h, a, b, c, d, e, f, 0x4d2c6dfc, w[2], w[0], w[11], w[3]);
-        round!(f, g, h, a, b, c, d, e, 0x53380d13, w[3], w[1], w[12], w[4]);
-        round!(e, f, g, h, a, b, c, d, 0x650a7354, w[4], w[2], w[13], w[5]);
-        round!(d, e, f, g, h, a, b, c, 0x766a0abb, w[5], w[3], w[14], w[6]);
-        round!(c, d, e, f, g, h, a, b, 0x81c2c92e, w[6], w[4], w[15], w[7]);
-        round!(b, c, d, e, f, g, h, a, 0x92722c85, w[7], w[5], w[0], w[8]);
-        round!(a, b, c, d, e, f, g, h, 0xa2bfe8a1, w[8], w[6], w[1], w[9]);
-        round!(h, a, b, c, d, e, f, g, 0xa81a664b, w[9], w[7], w[2], w[10]);
-        round!(g, h, a, b, c, d, e, f, 0xc24b8b70, w[10], w[8], w[3], w[11]);
-        round!(f, g, h, a, b, c, d, e, 0xc76c51a3, w[11], w[9], w[4], w[12]);
-        round!(e, f, g, h, a, b, c, d, 0xd192e819, w[12], w[10], w[5], w[13]);
-        round!(d, e, f, g, h, a, b, c, 0xd6990624, w[13], w[11], w[6], w[14]);
-        round!(c, d, e, f, g, h, a, b, 0xf40e3585, w[14], w[12], w[7], w[15]);
-        round!(b, c, d, e, f, g, h, a, 0x106aa070, w[15], w[13], w[8], w[0]);
-
-        round!(a, b, c, d, e, f, g, h, 0x19a4c116, w[0], w[14], w[9], w[1]);
-        round!(h, a, b, c, d, e, f, g, 0x1e376c08, w[1], w[15], w[10], w[2]);
-        round!(g, h, a, b, c, d, e, f, 0x2748774c, w[2], w[0], w[11], w[3]);
-        round!(f, g, h, a, b, c, d, e, 0x34b0bcb5, w[3], w[1], w[12], w[4]);
-        round!(e, f, g, h, a, b, c, d, 0x391c0cb3, w[4], w[2], w[13], w[5]);
-        round!(d, e, f, g, h, a, b, c, 0x4ed8aa4a, w[5], w[3], w[14], w[6]);
-        round!(c, d, e, f, g, h, a, b, 0x5b9cca4f, w[6], w[4], w[15], w[7]);
-        round!(b, c, d, e, f, g, h, a, 0x682e6ff3, w[7], w[5], w[0], w[8]);
-        round!(a, b, c, d, e, f, g, h, 0x748f82ee, w[8], w[6], w[1], w[9]);
-        round!(h, a, b, c, d, e, f, g, 0x78a5636f, w[9], w[7], w[2], w[10]);
-        round!(g, h, a, b, c, d, e, f, 0x84c87814, w[10], w[8], w[3], w[11]);
-        round!(f, g, h, a, b, c, d, e, 0x8cc70208, w[11], w[9], w[4], w[12]);
-        round!(e, f, g, h, a, b, c, d, 0x90befffa, w[12], w[10], w[5], w[13]);
-        round!(d, e, f, g, h, a, b, c, 0xa4506ceb, w[13], w[11], w[6], w[14]);
-        round!(c, d, e, f, g, h, a, b, 0xbef9a3f7, w[14], w[12], w[7], w[15]);
-        round!(b, c, d, e, f, g, h, a, 0xc67178f2, w[15], w[13], w[8], w[0]);
-
-        self.h[0] = self.h[0].wrapping_add(a);
-        self.h[1] = self.h[1].wrapping_add(b);
-        self.h[2] = self.h[2].wrapping_add(c);
-        self.h[3] = self.h[3].wrapping_add(d);
-        self.h[4] = self.h[4].wrapping_add(e);
-        self.h[5] = self.h[5].wrapping_add(f);
-        self.h[6] = self.h[6].wrapping_add(g);
-        self.h[7] = self.h[7].wrapping_add(h);
+    #[test]
+    fn compress_from_midstate_matches_software() {
+        use core::array;
+
+        use super::super::{compress, compress_software, HashEngine, Midstate};
+
+        // Arbitrary midstates, not reachable by hashing anything in practice.
+        for seed in 0..64_u32 {
+            let bytes: [u8; 32] = array::from_fn(|i| (seed * 97 + i as u32 * 31) as u8);
+            let block: [u8; 64] = array::from_fn(|i| (seed * 13 + i as u32 * 7) as u8);
+            let midstate = Midstate::new(bytes, 64 * u64::from(seed));
+
+            let mut state = HashEngine::from_midstate(midstate).h;
+            let mut software = state;
+            compress(&mut state, &block);
+            compress_software(&mut software, &block);
+            assert_eq!(state, software);
+
+            let (bytes, _) = Midstate::process_one_block(midstate, &block).to_parts();
+            let words: [u32; 8] =
+                array::from_fn(|i| u32::from_be_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap()));
+            assert_eq!(words, software);
+        }
     }
 }
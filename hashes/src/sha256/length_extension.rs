@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! SHA256 length extension, for security tooling and education.
+//!
+//! SHA256 outputs its full internal state: the digest of a message *is* the midstate after its
+//! last (padded) block. Knowing only `sha256(secret || msg)` and the length of `secret || msg`,
+//! anyone can resume hashing from that state and compute
+//! `sha256(secret || msg || glue || suffix)` for a `suffix` of their choice, where `glue` is the
+//! SHA256 padding of the original message. The secret is never needed.
+//!
+//! This is why `sha256(secret || msg)` is **not** a secure MAC, use [`crate::hmac`] instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin_hashes::sha256;
+//! use bitcoin_hashes::sha256::length_extension::{self, LengthExtendable};
+//!
+//! // The victim computes a naive MAC.
+//! let mut engine = LengthExtendable::new();
+//! engine.input(b"secret");
+//! engine.input(b"amount=10");
+//! let (mac, len) = engine.finalize();
+//!
+//! // The attacker knows `mac`, `len` and the message but not the secret.
+//! let (forged, glue) = length_extension::extend(mac, len, b"&amount=1000");
+//!
+//! let mut forged_msg = b"amount=10".to_vec();
+//! forged_msg.extend_from_slice(&glue);
+//! forged_msg.extend_from_slice(b"&amount=1000");
+//! // The forgery verifies against the secret.
+//! assert_eq!(forged, sha256::Hash::hash(&[&b"secret"[..], &forged_msg].concat()));
+//! ```
+
+use alloc::vec::Vec;
+
+use super::{Hash, HashEngine, Midstate, BLOCK_SIZE};
+use crate::HashEngine as _;
+
+/// A SHA256 engine that also returns the number of bytes hashed when finalized.
+///
+/// The length is needed, along with the digest, to [`extend`] the hash.
+#[derive(Debug, Clone, Default)]
+pub struct LengthExtendable(HashEngine);
+
+impl LengthExtendable {
+    /// Constructs a new engine.
+    pub const fn new() -> Self { LengthExtendable(HashEngine::new()) }
+
+    /// Adds `data` to the engine.
+    pub fn input(&mut self, data: &[u8]) { self.0.input(data) }
+
+    /// Finalizes the engine, returning the digest and the number of bytes hashed.
+    pub fn finalize(self) -> (Hash, u64) { self.0.finalize_with_len() }
+}
+
+/// Computes the hash of the original message extended with `suffix`, without knowing it.
+///
+/// `original_hash` is the SHA256 of a message of `original_len` bytes. Returns the SHA256 of
+/// `message || glue || suffix` and `glue`, the padding SHA256 applied to the original message.
+pub fn extend(original_hash: Hash, original_len: u64, suffix: &[u8]) -> (Hash, Vec<u8>) {
+    let glue = padding(original_len);
+    let padded_len = original_len + glue.len() as u64;
+
+    let midstate = Midstate::new(original_hash.to_byte_array(), padded_len);
+    let mut engine = HashEngine::from_midstate(midstate);
+    engine.input(suffix);
+    (Hash::from_engine(engine), glue)
+}
+
+/// Returns the SHA256 padding of a message of `len` bytes.
+fn padding(len: u64) -> Vec<u8> {
+    let block_size = BLOCK_SIZE as u64;
+    let zeros = (block_size - (len % block_size + 9) % block_size) % block_size;
+
+    let mut glue = Vec::with_capacity(9 + zeros as usize);
+    glue.push(0x80);
+    glue.resize(1 + zeros as usize, 0);
+    glue.extend_from_slice(&len.wrapping_mul(8).to_be_bytes());
+    glue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extend_matches_full_hash() {
+        let data: [u8; 200] = core::array::from_fn(|i| i as u8);
+        for len in [0, 1, 55, 56, 63, 64, 65, 119, 200] {
+            let mut engine = LengthExtendable::new();
+            engine.input(&data[..len]);
+            let (hash, hashed) = engine.finalize();
+            assert_eq!(hashed, len as u64);
+            assert_eq!(hash, Hash::hash(&data[..len]));
+
+            for suffix in [&b""[..], b"suffix", &[0xab; 100]] {
+                let (extended, glue) = extend(hash, hashed, suffix);
+                assert_eq!((len + glue.len()) % BLOCK_SIZE, 0);
+                assert_eq!(extended, Hash::hash(&[&data[..len], &glue, suffix].concat()));
+            }
+        }
+    }
+}
@@ -5,13 +5,16 @@
 #[cfg(bench)]
 mod benches;
 mod crypto;
+#[cfg(feature = "alloc")]
+pub mod length_extension;
 #[cfg(bench)]
 mod tests;
 
 use core::{cmp, convert, fmt};
+
 use internals::slice::SliceExt;
 
-use crate::{incomplete_block_len, sha256d, HashEngine as _};
+use crate::{incomplete_block_len, sha256d, siphash24, HashEngine as _};
 #[cfg(doc)]
 use crate::{sha256t, sha256t_tag};
 
@@ -21,22 +24,31 @@
     "Output of the SHA256 hash function."
 }
 
-#[cfg(not(hashes_fuzz))]
-fn from_engine(mut e: HashEngine) -> Hash {
-    // pad buffer with a single 1-bit then all 0s, until there are exactly 8 bytes remaining
-    let n_bytes_hashed = e.bytes_hashed;
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        const EMPTY: [u8; 32] = crate::hex_to_array(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            false,
+        );
+        const ABC: [u8; 32] = crate::hex_to_array(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            false,
+        );
+        const TWO_BLOCK: [u8; 32] = crate::hex_to_array(
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            false,
+        );
 
-    let zeroes = [0; BLOCK_SIZE - 8];
-    e.input(&[0x80]);
-    if incomplete_block_len(&e) > zeroes.len() {
-        e.input(&zeroes);
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
     }
-    let pad_length = zeroes.len() - incomplete_block_len(&e);
-    e.input(&zeroes[..pad_length]);
-    debug_assert_eq!(incomplete_block_len(&e), zeroes.len());
+}
 
-    e.input(&(8 * n_bytes_hashed).to_be_bytes());
-    debug_assert_eq!(incomplete_block_len(&e), 0);
+#[cfg(not(hashes_fuzz))]
+fn from_engine(mut e: HashEngine) -> Hash {
+    crate::internal_macros::trace_span!("sha256::from_engine", bytes_hashed = e.bytes_hashed);
+
+    let length = e.bytes_hashed.wrapping_mul(8).to_be_bytes();
+    crate::md_pad(&mut e, &length);
 
     Hash(e.midstate_unchecked().bytes)
 }
@@ -44,9 +56,10 @@ fn from_engine(mut e: HashEngine) -> Hash {
 #[cfg(hashes_fuzz)]
 fn from_engine(e: HashEngine) -> Hash {
     let mut hash = e.midstate_unchecked().bytes;
-    if hash == [0; 32] {
-        // Assume sha256 is secure and never generate 0-hashes (which represent invalid
-        // secp256k1 secret keys, causing downstream application breakage).
+    // Assume sha256 is secure and never generate 0-hashes (which represent invalid secp256k1
+    // secret keys, causing downstream application breakage). Harnesses that want to probe zero
+    // hash handling can opt out with `--cfg=hashes_fuzz_allow_zero`.
+    if cfg!(not(hashes_fuzz_allow_zero)) && hash == [0; 32] {
         hash[0] = 1;
     }
     Hash(hash)
@@ -54,6 +67,278 @@ fn from_engine(e: HashEngine) -> Hash {
 
 const BLOCK_SIZE: usize = 64;
 
+/// Runs the SHA256 compression function once, updating `state` with a single 64-byte `block`.
+///
+/// **This is not a hash function.** No padding or length encoding is done, the caller is
+/// responsible for all of that. This is the raw primitive used internally by [`HashEngine`] and is
+/// only exposed for people implementing specialized constructions on top of SHA256. If you just
+/// want to hash some data use [`Hash::hash`] or [`HashEngine`] instead.
+///
+/// Uses the SHA-NI instructions when available at runtime (requires the `std` feature) and falls
+/// back to a software implementation otherwise. With the `backend-sha2` feature enabled the RustCrypto
+/// `sha2` crate is used instead.
+pub fn compress(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) { crypto::compress(state, block) }
+
+/// Runs the SHA256 compression function using the portable software implementation.
+///
+/// Computes the same result as [`compress`] but never uses SHA-NI or the `backend-sha2` crate,
+/// regardless of enabled features and CPU support. This allows differential testing and fuzzing
+/// of the accelerated backend against the reference one, e.g. seeding a [`HashEngine`] with a
+/// random [`Midstate`] and comparing a single compression step.
+///
+/// ```
+/// use bitcoin_hashes::sha256;
+///
+/// let block = [0xab; 64];
+/// let mut state = [0x0123_4567; 8];
+/// let mut software = state;
+/// sha256::compress(&mut state, &block);
+/// sha256::compress_software(&mut software, &block);
+/// assert_eq!(state, software);
+/// ```
+pub fn compress_software(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    crypto::software_compress(state, block)
+}
+
+/// Returns `true` if the software implementation of [`compress`] was built for code size.
+///
+/// This is decided at compile time by the `small-hash` feature, which replaces the unrolled
+/// rounds with a loop. The compact version is noticeably slower but considerably smaller, useful
+/// on embedded targets. It has no effect when the `backend-sha2` feature is enabled and does not
+/// affect the SHA-NI code path, which is still selected at runtime when available.
+///
+/// Intended for diagnostics, e.g. to confirm that a build actually uses the compact code. Both
+/// versions compute the same hashes.
+///
+/// ```
+/// use bitcoin_hashes::sha256;
+///
+/// let mode = if sha256::code_size_mode() { "small" } else { "fast" };
+/// println!("sha256 software implementation: {}", mode);
+/// ```
+pub const fn code_size_mode() -> bool { crypto::SMALL_HASH }
+
+/// Hashes each input and checks it against the expected hash.
+///
+/// Returns one `bool` per pair, in order. Comparison is not constant time, this is intended for
+/// verifying public data. Use [`verify_many_fixed_time`] if that is required.
+#[cfg(feature = "alloc")]
+pub fn verify_many(pairs: &[(&[u8], Hash)]) -> alloc::vec::Vec<bool> {
+    pairs.iter().map(|(data, expected)| Hash::hash(data) == *expected).collect()
+}
+
+/// Hashes each input and checks it against the expected hash using [`crate::cmp::fixed_time_eq`].
+///
+/// Returns one `bool` per pair, in order.
+#[cfg(feature = "alloc")]
+pub fn verify_many_fixed_time(pairs: &[(&[u8], Hash)]) -> alloc::vec::Vec<bool> {
+    pairs
+        .iter()
+        .map(|(data, expected)| {
+            crate::cmp::fixed_time_eq(Hash::hash(data).as_byte_array(), expected.as_byte_array())
+        })
+        .collect()
+}
+
+/// Inputs `data_a` into `a` and `data_b` into `b` and finalizes both engines.
+///
+/// Produces the same hashes as doing so one engine at a time but runs the compressions of both
+/// engines side by side where possible, see [`crypto::compress2`].
+#[cfg(feature = "alloc")]
+#[cfg(not(hashes_fuzz))]
+pub(crate) fn finalize_pair(
+    mut a: HashEngine,
+    mut data_a: &[u8],
+    mut b: HashEngine,
+    mut data_b: &[u8],
+) -> (Hash, Hash) {
+    /// Returns the padded final block(s) of `e` and their length.
+    fn padded_tail(e: &HashEngine) -> ([u8; 2 * BLOCK_SIZE], usize) {
+        let len = incomplete_block_len(e);
+        let mut tail = [0; 2 * BLOCK_SIZE];
+        tail[..len].copy_from_slice(&e.buffer[..len]);
+        tail[len] = 0x80;
+        let n = if len < BLOCK_SIZE - 8 { BLOCK_SIZE } else { 2 * BLOCK_SIZE };
+        tail[n - 8..n].copy_from_slice(&(8 * e.bytes_hashed).to_be_bytes());
+        (tail, n)
+    }
+
+    if incomplete_block_len(&a) == 0 && incomplete_block_len(&b) == 0 {
+        let n = cmp::min(data_a.len(), data_b.len()) / BLOCK_SIZE;
+        let (head_a, head_b) = (&data_a[..n * BLOCK_SIZE], &data_b[..n * BLOCK_SIZE]);
+        let blocks = head_a.bitcoin_as_chunks().0.iter().zip(head_b.bitcoin_as_chunks().0);
+        for (block_a, block_b) in blocks {
+            crypto::compress2(&mut a.h, block_a, &mut b.h, block_b);
+        }
+        a.bytes_hashed += (n * BLOCK_SIZE) as u64;
+        b.bytes_hashed += (n * BLOCK_SIZE) as u64;
+        data_a = &data_a[n * BLOCK_SIZE..];
+        data_b = &data_b[n * BLOCK_SIZE..];
+    }
+    a.input(data_a);
+    b.input(data_b);
+
+    let (tail_a, len_a) = padded_tail(&a);
+    let (tail_b, len_b) = padded_tail(&b);
+    let (blocks_a, blocks_b) =
+        (tail_a[..len_a].bitcoin_as_chunks().0, tail_b[..len_b].bitcoin_as_chunks().0);
+    crypto::compress2(&mut a.h, &blocks_a[0], &mut b.h, &blocks_b[0]);
+    match (blocks_a.get(1), blocks_b.get(1)) {
+        (Some(block_a), Some(block_b)) => crypto::compress2(&mut a.h, block_a, &mut b.h, block_b),
+        (Some(block_a), None) => compress(&mut a.h, block_a),
+        (None, Some(block_b)) => compress(&mut b.h, block_b),
+        (None, None) => {}
+    }
+
+    (Hash(a.midstate_unchecked().bytes), Hash(b.midstate_unchecked().bytes))
+}
+
+/// Inputs `data_a` into `a` and `data_b` into `b` and finalizes both engines.
+#[cfg(feature = "alloc")]
+#[cfg(hashes_fuzz)]
+pub(crate) fn finalize_pair(
+    mut a: HashEngine,
+    data_a: &[u8],
+    mut b: HashEngine,
+    data_b: &[u8],
+) -> (Hash, Hash) {
+    a.input(data_a);
+    b.input(data_b);
+    (Hash::from_engine(a), Hash::from_engine(b))
+}
+
+/// Hashes the concatenation of `a` and `b` after sorting them lexicographically.
+///
+/// Both argument orders produce the same hash, i.e. `sha256(min(a, b) || max(a, b))`. This is the
+/// node hash used by trees where the parties don't agree on the position of the children.
+pub fn hash_sorted_pair(a: &[u8; 32], b: &[u8; 32]) -> Hash {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut engine = HashEngine::new();
+    engine.input(first);
+    engine.input(second);
+    Hash::from_engine(engine)
+}
+
+/// Hashes any number of byte chunks together, the chunks may be of different types.
+///
+/// Each argument is fed into a single engine in order using its `AsRef<[u8]>` implementation, e.g.
+/// slices, arrays, vectors, strings and other hashes. This is the same as inputting each chunk into
+/// a [`HashEngine`] by hand.
+///
+/// ```
+/// use bitcoin_hashes::{sha256, sha256d};
+///
+/// let prev = sha256d::Hash::hash(b"prev");
+/// let hash = sha256::hash_all!(prev, [0u8; 4], b"data", "text");
+/// let chunks: [&[u8]; 4] = [prev.as_ref(), &[0; 4], b"data", b"text"];
+/// assert_eq!(hash, sha256::Hash::hash_byte_chunks(chunks));
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _sha256_hash_all {
+    ($($chunk:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut engine = $crate::sha256::HashEngine::new();
+        $(
+            $crate::HashEngine::input(
+                &mut engine,
+                $crate::_export::_core::convert::AsRef::<[u8]>::as_ref(&$chunk),
+            );
+        )*
+        $crate::sha256::Hash::from_engine(engine)
+    }};
+}
+#[doc(inline)]
+pub use _sha256_hash_all as hash_all;
+
+/// Hashes the first `len` bytes of `buf` while hiding `len`.
+///
+/// `buf.len()` is the public upper bound on the length, e.g. the size of a passphrase buffer, and
+/// `len` is treated as secret. Always runs `ceil((buf.len() + 9) / 64)` compressions and reads
+/// every byte of `buf`, bytes from `len` onwards are masked out rather than skipped. Padding and
+/// the result are selected with masks, so neither branches nor memory accesses depend on `len`.
+/// The output is the same as `Hash::hash(&buf[..len])`. This is best effort, it is intended for
+/// secret length inputs such as passphrases and comes at the cost of always doing the work for
+/// the whole buffer.
+///
+/// # Panics
+///
+/// If `len > buf.len()` or if the padded length of `buf` overflows `usize`.
+pub fn hash_padded_ct(buf: &[u8], len: usize) -> Hash {
+    /// Returns all ones if `a < b` and zero otherwise, both must be at most `isize::MAX`.
+    fn lt(a: usize, b: usize) -> u32 {
+        0u32.wrapping_sub((a.wrapping_sub(b) >> (usize::BITS - 1)) as u32)
+    }
+    /// Returns all ones if `a == b` and zero otherwise, both must be at most `isize::MAX`.
+    fn eq(a: usize, b: usize) -> u32 { !(lt(a, b) | lt(b, a)) }
+
+    assert!(len <= buf.len(), "len is longer than the buffer");
+
+    let padded_len = buf.len().checked_add(9 + 63).expect("buffer too long to pad");
+    let n_blocks = padded_len / 64;
+    // Cannot overflow, `len + 9 + 63 <= padded_len`.
+    let last_block = (len + 9 + 63) / 64 - 1;
+    let bit_len = (8 * len as u64).to_be_bytes();
+
+    let mut state = HashEngine::new().h;
+    let mut out = [0u32; 8];
+    for i in 0..n_blocks {
+        let is_last = eq(i, last_block);
+
+        let mut block = [0u8; BLOCK_SIZE];
+        for (j, byte) in block.iter_mut().enumerate() {
+            let pos = i * BLOCK_SIZE + j;
+            // Only branches on the public `buf.len()`.
+            let data = buf.get(pos).copied().unwrap_or(0);
+            *byte = (data & lt(pos, len) as u8) | (0x80 & eq(pos, len) as u8);
+            if j >= BLOCK_SIZE - 8 {
+                *byte |= bit_len[j - (BLOCK_SIZE - 8)] & is_last as u8;
+            }
+        }
+        compress(&mut state, &block);
+
+        for (out, word) in out.iter_mut().zip(state) {
+            *out |= word & is_last;
+        }
+    }
+
+    let mut bytes = [0; 32];
+    for (word, chunk) in out.iter().zip(bytes.chunks_exact_mut(4)) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    Hash(bytes)
+}
+
+/// Hashes many messages that start with the same prefix, processing the prefix only once.
+///
+/// Stores the midstate after all complete blocks of the prefix plus the remaining partial block,
+/// so hashing a suffix only compresses the blocks after the prefix.
+///
+/// ```
+/// use bitcoin_hashes::sha256;
+///
+/// let hasher = sha256::PrefixHasher::new(b"common header");
+/// assert_eq!(hasher.hash_suffix(b"message"), sha256::Hash::hash(b"common headermessage"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixHasher(HashEngine);
+
+impl PrefixHasher {
+    /// Constructs a new hasher for messages starting with `prefix`.
+    pub fn new(prefix: &[u8]) -> Self {
+        let mut engine = HashEngine::new();
+        engine.input(prefix);
+        PrefixHasher(engine)
+    }
+
+    /// Returns the hash of `prefix || suffix`.
+    pub fn hash_suffix(&self, suffix: &[u8]) -> Hash {
+        let mut engine = self.0.clone();
+        engine.input(suffix);
+        Hash::from_engine(engine)
+    }
+}
+
 /// Engine to compute SHA256 hash function.
 #[derive(Debug, Clone)]
 pub struct HashEngine {
@@ -62,6 +347,28 @@ pub struct HashEngine {
     bytes_hashed: u64,
 }
 
+/// Two engines are equal if they have the same state, the same number of bytes hashed and the same
+/// buffered partial block.
+///
+/// Only the buffered prefix of the internal buffer is compared, bytes left over from previously
+/// processed blocks are stale and do not affect equality.
+impl PartialEq for HashEngine {
+    #[cfg(not(hashes_fuzz))]
+    fn eq(&self, other: &Self) -> bool {
+        let len = incomplete_block_len(self);
+        self.h == other.h
+            && self.bytes_hashed == other.bytes_hashed
+            && self.buffer[..len] == other.buffer[..len]
+    }
+
+    #[cfg(hashes_fuzz)]
+    fn eq(&self, other: &Self) -> bool {
+        self.h == other.h && self.bytes_hashed == other.bytes_hashed && self.buffer == other.buffer
+    }
+}
+
+impl Eq for HashEngine {}
+
 impl HashEngine {
     /// Constructs a new SHA256 hash engine.
     pub const fn new() -> Self {
@@ -78,10 +385,13 @@ pub const fn new() -> Self {
     /// Constructs a new [`HashEngine`] from a [`Midstate`].
     ///
     /// Please see docs on [`Midstate`] before using this function.
-    pub fn from_midstate(midstate: Midstate) -> HashEngine {
+    pub const fn from_midstate(midstate: Midstate) -> HashEngine {
+        let b = midstate.bytes;
         let mut ret = [0; 8];
-        for (ret_val, midstate_bytes) in ret.iter_mut().zip(midstate.as_ref().bitcoin_as_chunks().0) {
-            *ret_val = u32::from_be_bytes(*midstate_bytes);
+        let mut i = 0;
+        while i < 8 {
+            ret[i] = u32::from_be_bytes([b[4 * i], b[4 * i + 1], b[4 * i + 2], b[4 * i + 3]]);
+            i += 1;
         }
 
         HashEngine { buffer: [0; BLOCK_SIZE], h: ret, bytes_hashed: midstate.bytes_hashed }
@@ -105,6 +415,12 @@ pub fn midstate(&self) -> Result<Midstate, MidstateError> {
         Ok(self.midstate_unchecked())
     }
 
+    /// Returns a copy of the eight internal state words.
+    ///
+    /// Intended for testing and debugging only. The state is only updated once a full block has
+    /// been input, any bytes of a trailing partial block are buffered and not reflected here.
+    pub const fn state_words(&self) -> [u32; 8] { self.h }
+
     // Does not check that `HashEngine::can_extract_midstate`.
     #[cfg(not(hashes_fuzz))]
     fn midstate_unchecked(&self) -> Midstate {
@@ -124,6 +440,25 @@ fn midstate_unchecked(&self) -> Midstate {
     }
 }
 
+impl HashEngine {
+    /// Returns the complete state of this engine, including any buffered partial block.
+    ///
+    /// Unlike [`Self::midstate`] this works regardless of how many bytes have been input.
+    pub fn to_state(&self) -> EngineState {
+        let mut buffer = [0; BLOCK_SIZE];
+        let buffered = incomplete_block_len(self);
+        buffer[..buffered].copy_from_slice(&self.buffer[..buffered]);
+        EngineState { h: self.h, bytes_hashed: self.bytes_hashed, buffer }
+    }
+
+    /// Constructs a new engine from a previously saved [`EngineState`].
+    ///
+    /// The returned engine continues exactly where the engine `state` was taken from left off.
+    pub fn from_state(state: EngineState) -> Self {
+        HashEngine { buffer: state.buffer, h: state.h, bytes_hashed: state.bytes_hashed }
+    }
+}
+
 impl Default for HashEngine {
     fn default() -> Self { Self::new() }
 }
@@ -143,6 +478,201 @@ impl Hash {
     #[must_use]
     pub fn hash_again(&self) -> sha256d::Hash { sha256d::Hash::from_byte_array(hash(&self.0).0) }
 
+    /// Hashes `data`, writing the result into `out`.
+    ///
+    /// Equivalent to `*out = Hash::hash(data)`, intended for filling preallocated storage in
+    /// batch pipelines.
+    #[inline]
+    pub fn hash_into(data: &[u8], out: &mut Self) { *out = Self::hash(data) }
+
+    /// Returns the SipHash-2-4 of this hash's bytes, keyed with `k0` and `k1`.
+    ///
+    /// Useful to derive randomized table keys from hashes that may be chosen by an adversary.
+    /// Equivalent to [`siphash24::Hash::hash_to_u64_with_keys`] over [`Self::as_byte_array`].
+    pub fn siphash(&self, k0: u64, k1: u64) -> u64 {
+        siphash24::Hash::hash_to_u64_with_keys(k0, k1, &self.0)
+    }
+
+    /// Hashes the bytes encoded by the hex string `s`.
+    ///
+    /// Equivalent to decoding `s` and hashing the result but decodes on the fly, without
+    /// allocating, which keeps the memory use low for large hex blobs (e.g. raw transactions
+    /// returned by RPC). Both upper and lower case digits are accepted.
+    ///
+    /// # Errors
+    ///
+    /// If `s` has an odd length or contains a character that is not a hex digit.
+    pub fn hash_hex(s: &str) -> Result<Self, HexError> {
+        fn nibble(c: u8) -> Option<u8> {
+            match c {
+                b'0'..=b'9' => Some(c - b'0'),
+                b'a'..=b'f' => Some(c - b'a' + 10),
+                b'A'..=b'F' => Some(c - b'A' + 10),
+                _ => None,
+            }
+        }
+
+        let s = s.as_bytes();
+        if s.len() % 2 != 0 {
+            return Err(HexError::OddLength { len: s.len() });
+        }
+
+        let mut engine = HashEngine::new();
+        let mut buf = [0; BLOCK_SIZE];
+        for (i, chunk) in s.chunks(2 * BLOCK_SIZE).enumerate() {
+            let decoded = &mut buf[..chunk.len() / 2];
+            for (j, (byte, pair)) in decoded.iter_mut().zip(chunk.chunks_exact(2)).enumerate() {
+                let pos = i * 2 * BLOCK_SIZE + 2 * j;
+                let invalid = |offset: usize| HexError::InvalidChar {
+                    pos: pos + offset,
+                    invalid: pair[offset],
+                };
+                *byte = nibble(pair[0]).ok_or_else(|| invalid(0))? << 4
+                    | nibble(pair[1]).ok_or_else(|| invalid(1))?;
+            }
+            engine.input(decoded);
+        }
+        Ok(Self::from_engine(engine))
+    }
+
+    /// Hashes at most `limit` bytes read from `reader`.
+    ///
+    /// If `reader` runs out of data first, everything that was read is hashed. No more than
+    /// `limit` bytes are ever read from `reader`.
+    #[cfg(feature = "std")]
+    pub fn hash_reader_limited<R: std::io::Read>(reader: R, limit: u64) -> std::io::Result<Self> {
+        let mut engine = HashEngine::new();
+        std::io::copy(&mut std::io::Read::take(reader, limit), &mut engine)?;
+        Ok(Self::from_engine(engine))
+    }
+
+    /// Hashes the contents of the file at `path`.
+    ///
+    /// The file is read in chunks, see `hash_file_mmap` for a memory mapped version. If the file
+    /// is modified while it is being hashed the result is unspecified.
+    #[cfg(feature = "std")]
+    pub fn hash_file(path: &std::path::Path) -> std::io::Result<Self> {
+        Self::hash_reader_limited(std::fs::File::open(path)?, u64::MAX)
+    }
+
+    /// Hashes the contents of the file at `path` through a memory map.
+    ///
+    /// The file is mapped and hashed in one go, with the kernel advised that it is read
+    /// sequentially. Falls back to reading the file if it can't be mapped, e.g. on platforms
+    /// without memory maps or for special files.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, until this
+    /// function returns. The mapped memory is read as a byte slice, so concurrent modification is
+    /// undefined behavior. Use [`Self::hash_file`] if that can't be guaranteed.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn hash_file_mmap(path: &std::path::Path) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: The caller guarantees that the file is not modified while the map is alive, it
+        // is dropped before returning.
+        match unsafe { memmap2::Mmap::map(&file) } {
+            Ok(map) => {
+                // Only a hint, hashing works the same if it fails.
+                #[cfg(unix)]
+                let _ = map.advise(memmap2::Advice::Sequential);
+                Ok(Self::hash(&map))
+            }
+            Err(_) => Self::hash_reader_limited(&file, u64::MAX),
+        }
+    }
+
+    /// Returns the bytes of this hash with every bit after the first `bits` bits set to zero.
+    ///
+    /// Bits are counted in the order of the SHA256 output: starting at the first byte, most
+    /// significant bit first. E.g. with `bits = 12` the first byte is kept, the high nibble of the
+    /// second byte is kept and everything else is zeroed. Values of `bits` of 256 or more return
+    /// all bytes unchanged.
+    pub fn truncate_bits(&self, bits: usize) -> [u8; 32] {
+        let mut bytes = self.0;
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let keep = bits.saturating_sub(8 * i).min(8);
+            *byte &= !(0xff_u8.checked_shr(keep as u32).unwrap_or(0));
+        }
+        bytes
+    }
+
+    /// Reduces this hash, interpreted as a 256-bit big-endian integer, modulo `modulus`.
+    ///
+    /// Both `modulus` and the returned value are big-endian. Runs in constant time with respect to
+    /// the hash and the modulus. Useful for "hash then reduce" constructions, e.g. reducing modulo
+    /// the secp256k1 group order.
+    ///
+    /// # Panics
+    ///
+    /// If `modulus` is zero.
+    pub fn reduce_mod(&self, modulus: [u8; 32]) -> [u8; 32] {
+        fn to_limbs(bytes: &[u8; 32]) -> [u64; 4] {
+            let mut limbs = [0; 4];
+            for (limb, chunk) in limbs.iter_mut().zip(bytes.bitcoin_as_chunks::<8>().0) {
+                *limb = u64::from_be_bytes(*chunk);
+            }
+            limbs
+        }
+
+        assert!(modulus != [0; 32], "modulus must not be zero");
+
+        // Binary long division, limbs are most significant first.
+        let m = to_limbs(&modulus);
+        let mut r = [0u64; 4];
+        for byte in self.0 {
+            for i in (0..8).rev() {
+                let bit = u64::from((byte >> i) & 1);
+
+                // r = 2r + bit, keeping the bit shifted out.
+                let carry = r[0] >> 63;
+                for j in 0..3 {
+                    r[j] = (r[j] << 1) | (r[j + 1] >> 63);
+                }
+                r[3] = (r[3] << 1) | bit;
+
+                // if carry or r >= m { r -= m }
+                let mut diff = [0u64; 4];
+                let mut borrow = 0;
+                for j in (0..4).rev() {
+                    let (d, b1) = r[j].overflowing_sub(m[j]);
+                    let (d, b2) = d.overflowing_sub(borrow);
+                    diff[j] = d;
+                    borrow = u64::from(b1 | b2);
+                }
+                let mask = 0u64.wrapping_sub(carry | (borrow ^ 1));
+                for j in 0..4 {
+                    r[j] = (diff[j] & mask) | (r[j] & !mask);
+                }
+            }
+        }
+
+        let mut out = [0u8; 32];
+        for (limb, chunk) in r.iter().zip(out.bitcoin_as_chunks_mut::<8>().0) {
+            *chunk = limb.to_be_bytes();
+        }
+        out
+    }
+
+    /// Hashes `data` once, returning both its SHA256 and its SHA256d hash.
+    ///
+    /// The input is only processed once, equivalent to calling [`Self::hash_again`] on the result
+    /// of [`Self::hash`].
+    pub fn hash_single_and_double(data: &[u8]) -> (Self, sha256d::Hash) {
+        let single = Self::hash(data);
+        (single, single.hash_again())
+    }
+
+    /// Hashes all the bytes yielded by the iterator.
+    ///
+    /// Bytes are input a block at a time, so there is no need to collect the iterator into an
+    /// intermediate buffer first.
+    pub fn hash_byte_iter<I: IntoIterator<Item = u8>>(bytes: I) -> Self {
+        let mut engine = Self::engine();
+        engine.extend(bytes);
+        Self::from_engine(engine)
+    }
+
     /// Computes hash from `bytes` in `const` context.
     ///
     /// Warning: this function is inefficient. It should be only used in `const` context.
@@ -200,12 +730,64 @@ impl Midstate {
     /// Deconstructs the [`Midstate`], returning the underlying byte array and number of bytes hashed.
     pub const fn to_parts(self) -> ([u8; 32], u64) { (self.bytes, self.bytes_hashed) }
 
-    /// Constructs a new midstate for tagged hashes.
+    /// Returns the number of bits hashed as big-endian bytes.
+    ///
+    /// This is the length encoding used in the SHA256 padding, i.e. `bytes_hashed * 8` modulo
+    /// 2^64. Useful when exporting the state to implementations that track bits rather than bytes.
+    pub const fn bit_length_be(&self) -> [u8; 8] { self.bytes_hashed.wrapping_mul(8).to_be_bytes() }
+
+    /// Returns `false` if this midstate looks corrupted or uninitialized.
+    ///
+    /// This is a cheap heuristic to sanity check midstates loaded from untrusted storage before
+    /// passing them to [`HashEngine::from_midstate`], **not** a cryptographic check. A `true`
+    /// result says nothing about what data, if any, was hashed to get to this midstate.
+    ///
+    /// Returns `false` if the state is all zeros, or if it is the SHA256 initial state while the
+    /// number of bytes hashed is not zero.
+    pub fn looks_initialized(&self) -> bool {
+        let iv = HashEngine::new().h;
+        let is_iv =
+            self.bytes.chunks_exact(4).zip(iv).all(|(bytes, word)| bytes == word.to_be_bytes());
+        self.bytes != [0; 32] && !(is_iv && self.bytes_hashed != 0)
+    }
+
+    /// Compares two midstates for equality in `const` context.
+    ///
+    /// Both the bytes and the number of bytes hashed are compared. This is **not** constant time.
+    pub const fn const_eq(&self, other: &Self) -> bool {
+        self.bytes_hashed == other.bytes_hashed && crate::cmp::const_eq(&self.bytes, &other.bytes)
+    }
+
+    /// Constructs a new midstate by running a single compression over `block`.
     ///
     /// Warning: this function is inefficient. It should be only used in `const` context.
     ///
+    /// The returned midstate has 64 bytes hashed and is the same as inputting `block` into a fresh
+    /// [`HashEngine`] and calling [`HashEngine::midstate`]. This is the general form of
+    /// [`Self::hash_tag`] for arbitrary single-block prefixes.
+    pub const fn from_first_block(block: &[u8; 64]) -> Self {
+        Self::compute_midstate_unoptimized(block, false)
+    }
+
+    /// Runs the compression function over `block`, starting from `state`.
+    ///
+    /// **This is not a hash function.** No padding or length encoding is done, this is the raw
+    /// Merkle-Damgård step: the returned midstate has 64 more bytes hashed than `state`. Useful for
+    /// experimenting with SHA256 based constructions, e.g. demonstrating length extension.
+    pub fn process_one_block(state: Midstate, block: &[u8; 64]) -> Self {
+        let mut engine = HashEngine::from_midstate(state);
+        engine.input(block);
+        engine.midstate_unchecked()
+    }
+
+    /// Constructs a new midstate for tagged hashes.
+    ///
+    /// Warning: this function is inefficient. It should be only used in `const` context, use
+    /// [`Self::hash_tag_fast`] at runtime.
+    ///
     /// Computes non-finalized hash of `sha256(tag) || sha256(tag)` for use in [`sha256t`]. It's
-    /// provided for use with [`sha256t`].
+    /// provided for use with [`sha256t`]. Note that `tag` is *not* used as a literal prefix, see
+    /// [`sha256t::raw_prefix_engine`] if that is what you need.
     #[must_use]
     pub const fn hash_tag(tag: &[u8]) -> Self {
         let hash = Hash::hash_unoptimized(tag);
@@ -217,6 +799,19 @@ pub const fn hash_tag(tag: &[u8]) -> Self {
         }
         Self::compute_midstate_unoptimized(&buf, false)
     }
+
+    /// Constructs a new midstate for tagged hashes, at runtime.
+    ///
+    /// Computes the same midstate as [`Self::hash_tag`] but using the optimized (SIMD capable)
+    /// implementation. Use this when the tag is only known at runtime, e.g. read from a config.
+    #[must_use]
+    pub fn hash_tag_fast(tag: &[u8]) -> Self {
+        let hash = Hash::hash(tag);
+        let mut engine = HashEngine::new();
+        engine.input(hash.as_byte_array());
+        engine.input(hash.as_byte_array());
+        engine.midstate_unchecked()
+    }
 }
 
 impl fmt::Debug for Midstate {
@@ -239,6 +834,196 @@ impl convert::AsRef<[u8]> for Midstate {
     fn as_ref(&self) -> &[u8] { &self.bytes }
 }
 
+/// Complete state of a SHA256 [`HashEngine`], used to suspend and resume hashing.
+///
+/// Obtained from [`HashEngine::to_state`] and turned back into an engine using
+/// [`HashEngine::from_state`]. Unlike [`Midstate`], this includes the bytes of any partially
+/// filled block and is thus valid at any point, not just on block boundaries.
+///
+/// The state contains no secrets beyond the input itself, however the buffered bytes *are* raw
+/// input so if the data being hashed is sensitive the saved state must be treated as such too.
+///
+/// With the `serde` feature enabled the state serializes to the bytes produced by
+/// [`EngineState::to_bytes`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EngineState {
+    h: [u32; 8],
+    bytes_hashed: u64,
+    // INVARIANT: bytes past `bytes_hashed % 64` are always zero.
+    buffer: [u8; BLOCK_SIZE],
+}
+
+impl EngineState {
+    /// Maximum length of the encoding returned by [`Self::to_bytes`].
+    pub const MAX_ENCODED_LEN: usize = 32 + 8 + BLOCK_SIZE;
+
+    /// Returns the number of bytes input into the engine this state was taken from.
+    pub const fn bytes_hashed(&self) -> u64 { self.bytes_hashed }
+
+    /// Returns the input bytes that were buffered in the engine but not yet compressed.
+    pub fn buffered_bytes(&self) -> &[u8] { &self.buffer[..self.buffered_len()] }
+
+    /// Encodes the state, returns the buffer and the number of bytes of it used.
+    ///
+    /// The encoding is the chaining value as big-endian `u32`s, followed by the number of bytes
+    /// hashed as a big-endian `u64`, followed by the buffered bytes.
+    pub fn to_bytes(self) -> ([u8; Self::MAX_ENCODED_LEN], usize) {
+        let mut ret = [0; Self::MAX_ENCODED_LEN];
+        for (val, ret_bytes) in self.h.iter().zip(ret[..32].bitcoin_as_chunks_mut::<4>().0) {
+            *ret_bytes = val.to_be_bytes();
+        }
+        ret[32..40].copy_from_slice(&self.bytes_hashed.to_be_bytes());
+        let len = 40 + self.buffered_len();
+        ret[40..len].copy_from_slice(self.buffered_bytes());
+        (ret, len)
+    }
+
+    /// Decodes a state previously encoded with [`Self::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// If the number of buffered bytes doesn't match the encoded number of bytes hashed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EngineStateError> {
+        if bytes.len() < 40 || bytes.len() > Self::MAX_ENCODED_LEN {
+            return Err(EngineStateError { invalid_len: bytes.len() });
+        }
+        let mut h = [0; 8];
+        for (val, h_bytes) in h.iter_mut().zip(bytes[..32].bitcoin_as_chunks::<4>().0) {
+            *val = u32::from_be_bytes(*h_bytes);
+        }
+        let bytes_hashed = u64::from_be_bytes(bytes[32..40].try_into().expect("slice is 8 bytes"));
+        let buffered = &bytes[40..];
+        if buffered.len() as u64 != bytes_hashed % BLOCK_SIZE as u64 {
+            return Err(EngineStateError { invalid_len: bytes.len() });
+        }
+        let mut buffer = [0; BLOCK_SIZE];
+        buffer[..buffered.len()].copy_from_slice(buffered);
+        Ok(EngineState { h, bytes_hashed, buffer })
+    }
+
+    fn buffered_len(&self) -> usize { (self.bytes_hashed % BLOCK_SIZE as u64) as usize }
+}
+
+impl fmt::Debug for EngineState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct Encoder<'a> {
+            bytes: &'a [u8],
+        }
+        impl fmt::Debug for Encoder<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { crate::debug_hex(self.bytes, f) }
+        }
+
+        f.debug_struct("EngineState")
+            .field("h", &self.h)
+            .field("length", &self.bytes_hashed)
+            .field("buffered", &Encoder { bytes: self.buffered_bytes() })
+            .finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for EngineState {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let (bytes, len) = self.to_bytes();
+        s.serialize_bytes(&bytes[..len])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EngineState {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de;
+
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = EngineState;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an encoded SHA256 engine state")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                EngineState::from_bytes(v).map_err(E::custom)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = [0; EngineState::MAX_ENCODED_LEN];
+                let mut len = 0;
+                while let Some(byte) = seq.next_element()? {
+                    if len == bytes.len() {
+                        return Err(de::Error::invalid_length(len + 1, &self));
+                    }
+                    bytes[len] = byte;
+                    len += 1;
+                }
+                self.visit_bytes(&bytes[..len])
+            }
+        }
+
+        d.deserialize_bytes(Visitor)
+    }
+}
+
+/// Invalid encoding of an [`EngineState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineStateError {
+    /// The length of the invalid encoding.
+    invalid_len: usize,
+}
+
+impl fmt::Display for EngineStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid engine state encoding length {}", self.invalid_len)
+    }
+}
+
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for EngineStateError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for EngineStateError {}
+    }
+}
+
+/// Invalid hex string passed to [`Hash::hash_hex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HexError {
+    /// The string has an odd number of characters.
+    OddLength {
+        /// The length of the string.
+        len: usize,
+    },
+    /// The string contains a character that is not a hex digit.
+    InvalidChar {
+        /// The position of the invalid character, in bytes.
+        pos: usize,
+        /// The invalid character (or byte of a multi-byte character).
+        invalid: u8,
+    },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HexError::OddLength { len } => write!(f, "odd length hex string: {}", len),
+            HexError::InvalidChar { pos, invalid } =>
+                write!(f, "invalid hex character 0x{:02x} at position {}", invalid, pos),
+        }
+    }
+}
+
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for HexError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for HexError {}
+    }
+}
+
 /// `Midstate` invariant violated (not a multiple of 64).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MidstateError {
@@ -256,5 +1041,11 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
-#[cfg(feature = "std")]
-impl std::error::Error for MidstateError {}
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for MidstateError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for MidstateError {}
+    }
+}
@@ -111,6 +111,53 @@ pub(crate) fn midstate() {
     );
 }
 
+#[test]
+fn engine_state_roundtrip() {
+    let data: [u8; 200] = array::from_fn(|i| i as u8);
+    let want = sha256::Hash::hash(&data);
+
+    for split in [0, 1, 63, 64, 65, 130, 200] {
+        let mut engine = sha256::Hash::engine();
+        engine.input(&data[..split]);
+
+        let state = engine.to_state();
+        assert_eq!(state.bytes_hashed(), split as u64);
+        assert_eq!(state.buffered_bytes(), &data[split - split % 64..split]);
+
+        let (bytes, len) = state.to_bytes();
+        let decoded = EngineState::from_bytes(&bytes[..len]).expect("valid encoding");
+        assert_eq!(decoded, state);
+
+        let mut resumed = sha256::HashEngine::from_state(decoded);
+        resumed.input(&data[split..]);
+        assert_eq!(sha256::Hash::from_engine(resumed), want);
+    }
+}
+
+#[test]
+fn engine_state_invalid_encoding() {
+    let mut engine = sha256::Hash::engine();
+    engine.input(&[0xab; 10]);
+    let (bytes, len) = engine.to_state().to_bytes();
+
+    assert!(EngineState::from_bytes(&bytes[..len]).is_ok());
+    assert!(EngineState::from_bytes(&bytes[..len - 1]).is_err());
+    assert!(EngineState::from_bytes(&bytes[..len + 1]).is_err());
+    assert!(EngineState::from_bytes(&bytes[..39]).is_err());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn engine_state_serde() {
+    let mut engine = sha256::Hash::engine();
+    engine.input(&[0xab; 70]);
+    let state = engine.to_state();
+
+    let json = serde_json::to_string(&state).expect("failed to serialize");
+    let decoded: EngineState = serde_json::from_str(&json).expect("failed to deserialize");
+    assert_eq!(decoded, state);
+}
+
 #[test]
 fn engine_with_state() {
     let mut engine = sha256::Hash::engine();
@@ -130,11 +177,10 @@ fn engine_with_state() {
     for data in data_vec {
         let mut engine = engine.clone();
         let mut midstate_engine = sha256::HashEngine::from_midstate(engine.midstate_unchecked());
-        assert_eq!(engine.h, midstate_engine.h);
-        assert_eq!(engine.bytes_hashed, midstate_engine.bytes_hashed);
+        assert_eq!(engine, midstate_engine);
         engine.input(data);
         midstate_engine.input(data);
-        assert_eq!(engine.h, midstate_engine.h);
+        assert_eq!(engine, midstate_engine);
         let hash1 = sha256::Hash::from_engine(engine);
         let hash2 = sha256::Hash::from_engine(midstate_engine);
         assert_eq!(hash1, hash2);
@@ -177,6 +223,48 @@ fn hash_unoptimized() {
     }
 }
 
+#[test]
+fn compress_single_block() {
+    // The padded empty message is a single block.
+    let mut block = [0u8; 64];
+    block[0] = 0x80;
+
+    let mut state = sha256::HashEngine::new().h;
+    compress(&mut state, &block);
+
+    let mut bytes = [0u8; 32];
+    for (word, chunk) in state.iter().zip(bytes.chunks_exact_mut(4)) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    assert_eq!(Hash::from_byte_array(bytes), Hash::hash(b""));
+}
+
+#[test]
+fn const_eq() {
+    const A: Hash = Hash::hash_unoptimized(b"TapLeaf");
+    const B: Hash = Hash::hash_unoptimized(b"TapBranch");
+    const _: () = assert!(A.const_eq(&A));
+    const _: () = assert!(!A.const_eq(&B));
+
+    assert!(A.const_eq(&Hash::hash(b"TapLeaf")));
+}
+
+#[test]
+fn hash_byte_iter() {
+    assert_eq!(Hash::hash_byte_iter(core::iter::empty()), Hash::hash(b""));
+
+    let bytes: [u8; 256] = array::from_fn(|i| i as u8);
+    for i in 0..=256 {
+        let bytes = &bytes[0..i];
+        assert_eq!(
+            Hash::hash_byte_iter(bytes.iter().copied()),
+            Hash::hash(bytes),
+            "hashes don't match for n_bytes_hashed {}",
+            i
+        );
+    }
+}
+
 // The midstate of an empty hash engine tagged with "TapLeaf".
 const TAP_LEAF_MIDSTATE: Midstate = Midstate::new(
     [
@@ -189,6 +277,24 @@ fn hash_unoptimized() {
 #[test]
 fn const_midstate() { assert_eq!(Midstate::hash_tag(b"TapLeaf"), TAP_LEAF_MIDSTATE,) }
 
+#[test]
+fn hash_tag_fast() {
+    assert_eq!(Midstate::hash_tag_fast(b"TapLeaf"), TAP_LEAF_MIDSTATE);
+    // Including tags longer than a block.
+    for tag in [&b""[..], b"BIP0340/challenge", &[0xab; 64], &[0xcd; 200]] {
+        assert_eq!(Midstate::hash_tag_fast(tag), Midstate::hash_tag(tag));
+    }
+}
+
+#[test]
+fn midstate_const_eq() {
+    const _: () = assert!(TAP_LEAF_MIDSTATE.const_eq(&Midstate::hash_tag(b"TapLeaf")));
+    const _: () = assert!(!TAP_LEAF_MIDSTATE.const_eq(&Midstate::hash_tag(b"TapBranch")));
+
+    let (bytes, _) = TAP_LEAF_MIDSTATE.to_parts();
+    assert!(!TAP_LEAF_MIDSTATE.const_eq(&Midstate::new(bytes, 128)));
+}
+
 #[test]
 #[cfg(feature = "alloc")]
 fn regression_midstate_debug_format() {
@@ -231,3 +337,309 @@ fn sha256_tests() {
         engine_with_state();
     }
 }
+
+#[test]
+#[cfg(feature = "alloc")]
+fn verify_many() {
+    let pairs: [(&[u8], Hash); 3] = [
+        (b"", Hash::hash(b"")),
+        (b"abc", Hash::hash(b"abc")),
+        (b"abd", Hash::hash(b"abc")),
+    ];
+    assert_eq!(sha256::verify_many(&pairs), [true, true, false]);
+    assert_eq!(sha256::verify_many_fixed_time(&pairs), [true, true, false]);
+}
+
+#[test]
+fn midstate_from_first_block() {
+    const BLOCK: [u8; 64] = [0xab; 64];
+    const MIDSTATE: Midstate = Midstate::from_first_block(&BLOCK);
+
+    let mut engine = sha256::HashEngine::new();
+    engine.input(&BLOCK);
+    assert_eq!(MIDSTATE, engine.midstate().unwrap());
+    assert_eq!(MIDSTATE.as_parts().1, 64);
+}
+
+#[test]
+fn const_engine_from_midstate() {
+    const MIDSTATE: Midstate = Midstate::hash_tag(b"TapLeaf");
+    const ENGINE: sha256::HashEngine = sha256::HashEngine::from_midstate(MIDSTATE);
+
+    assert_eq!(ENGINE.midstate().unwrap(), MIDSTATE);
+}
+
+#[test]
+fn hash_single_and_double() {
+    let data = [0xab_u8; 100];
+    let (single, double) = Hash::hash_single_and_double(&data);
+    assert_eq!(single, sha256::Hash::hash(&data));
+    assert_eq!(double, sha256d::Hash::hash(&data));
+}
+
+#[test]
+fn hash_into() {
+    let zero = Hash::from_byte_array([0; 32]);
+    let mut hashes = [zero; 2];
+    Hash::hash_into(b"abc", &mut hashes[1]);
+    assert_eq!(hashes, [zero, Hash::hash(b"abc")]);
+}
+
+#[test]
+fn hash_padded_ct() {
+    let data: [u8; 130] = array::from_fn(|i| i as u8);
+    for len in 0..=data.len() {
+        let want = Hash::hash(&data[..len]);
+        assert_eq!(sha256::hash_padded_ct(&data[..len], len), want);
+        assert_eq!(sha256::hash_padded_ct(&data, len), want);
+    }
+}
+
+#[test]
+fn hash_padded_ct_block_boundaries() {
+    // 55 bytes is the most that fits in one block with padding, 56 and 64 need two.
+    for max_len in [55, 56, 63, 64, 119, 120] {
+        let buf = [0xab_u8; 120];
+        for len in 0..=max_len {
+            assert_eq!(sha256::hash_padded_ct(&buf[..max_len], len), Hash::hash(&buf[..len]));
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn hash_padded_ct_too_long() { sha256::hash_padded_ct(&[0; 9], 10); }
+
+#[test]
+fn midstate_process_one_block() {
+    let (first, second) = ([0xab; 64], [0xcd; 64]);
+
+    let mut engine = sha256::HashEngine::new();
+    engine.input(&first);
+    engine.input(&second);
+
+    let state = Midstate::process_one_block(Midstate::from_first_block(&first), &second);
+    assert_eq!(state, engine.midstate().unwrap());
+    assert_eq!(state.as_parts().1, 128);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "exceeds u64::MAX bytes")]
+fn bytes_hashed_overflow() {
+    let mut engine = sha256::HashEngine::new();
+    engine.bytes_hashed = u64::MAX - 63;
+    engine.input(&[0; 64]);
+}
+
+#[test]
+#[cfg(feature = "hex")]
+fn reduce_mod() {
+    use crate::hex::FromHex as _;
+
+    fn reduce(hash: &str, modulus: &str) -> [u8; 32] {
+        let hash = Hash::from_byte_array(<[u8; 32]>::from_hex(hash).unwrap());
+        hash.reduce_mod(<[u8; 32]>::from_hex(modulus).unwrap())
+    }
+    fn want(hex: &str) -> [u8; 32] { <[u8; 32]>::from_hex(hex).unwrap() }
+
+    const MAX: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+    // secp256k1 group order and field prime.
+    const N: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+    const P: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+    const ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    assert_eq!(
+        reduce(MAX, N),
+        want("000000000000000000000000000000014551231950b75fc4402da1732fc9bebe")
+    );
+    assert_eq!(
+        reduce(MAX, P),
+        want("00000000000000000000000000000000000000000000000000000001000003d0")
+    );
+    assert_eq!(
+        reduce(ABC, "00000000000000000000000000000000000000000000000000000000000f4243"),
+        want("000000000000000000000000000000000000000000000000000000000001f16e")
+    );
+    assert_eq!(
+        reduce(ABC, "8000000000000000000000000000000000000000000000000000000000000000"),
+        want("3a7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+    );
+    assert_eq!(reduce(ABC, MAX), want(ABC));
+    assert_eq!(reduce(N, N), [0; 32]);
+}
+
+#[test]
+fn state_words() {
+    let mut engine = sha256::HashEngine::new();
+    let initial = engine.state_words();
+    engine.input(&[0xab; 63]);
+    assert_eq!(engine.state_words(), initial);
+
+    engine.input(&[0xab]);
+    let mut state = initial;
+    compress(&mut state, &[0xab; 64]);
+    assert_eq!(engine.state_words(), state);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn hash_reader_limited() {
+    let data: [u8; 300] = array::from_fn(|i| i as u8);
+
+    for limit in [0, 1, 64, 299, 300, 301, 1000] {
+        let mut reader = &data[..];
+        let hash = Hash::hash_reader_limited(&mut reader, limit).expect("reading a slice");
+        let read = data.len() - reader.len();
+        assert_eq!(read as u64, limit.min(300));
+        assert_eq!(hash, Hash::hash(&data[..read]));
+    }
+}
+
+#[test]
+fn midstate_bit_length_be() {
+    assert_eq!(Midstate::default().bit_length_be(), [0; 8]);
+    assert_eq!(TAP_LEAF_MIDSTATE.bit_length_be(), [0, 0, 0, 0, 0, 0, 0x02, 0x00]);
+    let midstate = Midstate::new([0; 32], 64 * 0x0102_0304);
+    assert_eq!(midstate.bit_length_be(), [0x00, 0x00, 0x00, 0x02, 0x04, 0x06, 0x08, 0x00]);
+}
+
+#[test]
+fn engine_eq_ignores_stale_buffer() {
+    let mut engine = sha256::HashEngine::new();
+    engine.input(&[0xab; 64]);
+    let mut resumed = sha256::HashEngine::from_midstate(engine.midstate().unwrap());
+
+    // `engine` still has the processed block in its buffer, `resumed` has zeros.
+    engine.input(&[1, 2, 3]);
+    resumed.input(&[1, 2, 3]);
+    assert_eq!(engine, resumed);
+
+    resumed.input(&[4]);
+    assert_ne!(engine, resumed);
+    engine.input(&[5]);
+    assert_ne!(engine, resumed);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn hash_all() {
+    let prev = sha256::Hash::hash(b"prev");
+    let vec = alloc::vec![1u8, 2, 3];
+    let slice: &[u8] = &[4, 5];
+
+    let mut engine = sha256::HashEngine::new();
+    engine.input(prev.as_ref());
+    engine.input(&[0; 4]);
+    engine.input(&vec);
+    engine.input(slice);
+    engine.input(b"text");
+    assert_eq!(sha256::hash_all!(prev, [0u8; 4], vec, slice, "text"), Hash::from_engine(engine));
+
+    assert_eq!(sha256::hash_all!(), Hash::hash(&[]));
+    assert_eq!(sha256::hash_all!(b"single",), Hash::hash(b"single"));
+}
+
+#[test]
+fn prefix_hasher() {
+    let data: [u8; 300] = array::from_fn(|i| (i * 11) as u8);
+    for prefix_len in [0, 1, 63, 64, 65, 128, 150] {
+        let (prefix, rest) = data.split_at(prefix_len);
+        let hasher = PrefixHasher::new(prefix);
+        for suffix_len in [0, 1, 55, 64, 100] {
+            let want = Hash::hash(&data[..prefix_len + suffix_len]);
+            assert_eq!(hasher.hash_suffix(&rest[..suffix_len]), want);
+        }
+    }
+}
+
+#[test]
+fn hash_sorted_pair() {
+    let a = [0x01; 32];
+    let mut b = [0x01; 32];
+    b[31] = 0x02;
+
+    let mut concat = [0; 64];
+    concat[..32].copy_from_slice(&a);
+    concat[32..].copy_from_slice(&b);
+    assert_eq!(sha256::hash_sorted_pair(&a, &b), Hash::hash(&concat));
+    assert_eq!(sha256::hash_sorted_pair(&b, &a), Hash::hash(&concat));
+    assert_eq!(sha256::hash_sorted_pair(&a, &a), Hash::hash(&[0x01; 64]));
+}
+
+#[test]
+fn truncate_bits() {
+    // sha256("abc") = ba7816bf 8f01cfea ...
+    let hash = Hash::hash(b"abc");
+    let mut want = [0; 32];
+    want[..2].copy_from_slice(&[0xba, 0x70]);
+    assert_eq!(hash.truncate_bits(12), want);
+
+    assert_eq!(hash.truncate_bits(0), [0; 32]);
+    assert_eq!(hash.truncate_bits(8)[..2], [0xba, 0x00]);
+    assert_eq!(hash.truncate_bits(256), hash.to_byte_array());
+    assert_eq!(hash.truncate_bits(1000), hash.to_byte_array());
+}
+
+#[test]
+#[cfg(all(feature = "self-test", feature = "std"))]
+fn self_test_passes() {
+    // Runs the self-test on first use, concurrently with whatever other tests do the same.
+    let handles: std::vec::Vec<_> =
+        (0..4).map(|_| std::thread::spawn(|| sha256::Hash::hash(&[0; 100]))).collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), sha256::Hash::hash(&[0; 100]));
+    }
+}
+
+#[test]
+fn hash_hex() {
+    use sha256::HexError;
+
+    let data: [u8; 300] = array::from_fn(|i| (i * 7) as u8);
+    let mut hex = [0; 600];
+    for (byte, pair) in data.iter().zip(hex.chunks_exact_mut(2)) {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        pair[0] = DIGITS[usize::from(byte >> 4)];
+        pair[1] = DIGITS[usize::from(byte & 0x0f)];
+    }
+    for len in [0, 1, 63, 64, 65, 128, 300] {
+        let s = core::str::from_utf8(&hex[..2 * len]).unwrap();
+        assert_eq!(Hash::hash_hex(s), Ok(Hash::hash(&data[..len])));
+    }
+
+    assert_eq!(Hash::hash_hex("616263"), Ok(Hash::hash(b"abc")));
+    assert_eq!(Hash::hash_hex("DEADbeef"), Ok(Hash::hash(&[0xde, 0xad, 0xbe, 0xef])));
+    assert_eq!(Hash::hash_hex("abc"), Err(HexError::OddLength { len: 3 }));
+    assert_eq!(Hash::hash_hex("ab0g"), Err(HexError::InvalidChar { pos: 3, invalid: b'g' }));
+
+    let mut bad = hex;
+    bad[200] = b' ';
+    let s = core::str::from_utf8(&bad).unwrap();
+    assert_eq!(Hash::hash_hex(s), Err(HexError::InvalidChar { pos: 200, invalid: b' ' }));
+}
+
+#[test]
+fn siphash() {
+    use crate::siphash24;
+
+    let hash = Hash::hash(b"abc");
+    for (k0, k1) in [(0, 0), (1, 2), (u64::MAX, 0x0123_4567_89ab_cdef)] {
+        let mut engine = siphash24::HashEngine::with_keys(k0, k1);
+        engine.input(hash.as_byte_array());
+        assert_eq!(hash.siphash(k0, k1), siphash24::Hash::from_engine(engine).to_u64());
+    }
+    assert_ne!(hash.siphash(1, 2), hash.siphash(2, 1));
+}
+
+#[test]
+fn midstate_looks_initialized() {
+    assert!(TAP_LEAF_MIDSTATE.looks_initialized());
+    assert!(sha256::HashEngine::new().midstate().unwrap().looks_initialized());
+
+    assert!(!Midstate::new([0; 32], 0).looks_initialized());
+    assert!(!Midstate::new([0; 32], 64).looks_initialized());
+
+    let (iv, _) = sha256::HashEngine::new().midstate().unwrap().to_parts();
+    assert!(!Midstate::new(iv, 64).looks_initialized());
+}
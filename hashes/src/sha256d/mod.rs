@@ -1,8 +1,25 @@
 // SPDX-License-Identifier: CC0-1.0
 
 //! SHA256d implementation (double SHA256).
+//!
+//! There is intentionally no conversion between [`sha256::Hash`] and [`Hash`], mixing them up is
+//! a real source of bugs. To go from a single to a double hash use [`sha256::Hash::hash_again`].
+//!
+//! ```compile_fail
+//! use bitcoin_hashes::{sha256, sha256d};
+//!
+//! let single = sha256::Hash::hash(b"some data");
+//! let _: sha256d::Hash = single.into();
+//! ```
+//!
+//! ```compile_fail
+//! use bitcoin_hashes::{sha256, sha256d};
+//!
+//! let double = sha256d::Hash::hash(b"some data");
+//! let _: sha256::Hash = double.into();
+//! ```
 
-use crate::sha256;
+use crate::{sha256, HashEngine as _};
 
 crate::internal_macros::general_hash_type! {
     256,
@@ -10,6 +27,38 @@
     "Output of the SHA256d hash function."
 }
 
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        // Displayed backwards.
+        const EMPTY: [u8; 32] = crate::hex_to_array(
+            "56944c5d3f98413ef45cf54545538103cc9f298e0575820ad3591376e2e0f65d",
+            true,
+        );
+        const ABC: [u8; 32] = crate::hex_to_array(
+            "58636c3ec08c12d55aedda056d602d5bcca72d8df6a69b519b72d32dc2428b4f",
+            true,
+        );
+        const TWO_BLOCK: [u8; 32] = crate::hex_to_array(
+            "af63952f8155cbb708b3b24997440992c95ebd5814fb843aac4d95687fe1ff0c",
+            true,
+        );
+
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
+    }
+}
+
+#[cfg(not(hashes_fuzz))]
+fn from_engine(e: HashEngine) -> Hash {
+    let state = second_hash_state(sha256::Hash::from_engine(e.0));
+
+    let mut ret = [0; 32];
+    for (word, chunk) in state.iter().zip(ret.chunks_exact_mut(4)) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    Hash(ret)
+}
+
+#[cfg(hashes_fuzz)]
 fn from_engine(e: HashEngine) -> Hash {
     let sha2 = sha256::Hash::from_engine(e.0);
     let sha2d = sha256::Hash::hash(sha2.as_byte_array());
@@ -19,6 +68,38 @@ fn from_engine(e: HashEngine) -> Hash {
     Hash(ret)
 }
 
+/// Computes the Bitcoin P2P message header checksum of `payload`.
+///
+/// This is the first four bytes of the SHA256d hash of `payload`. Use [`HashEngine::checksum`] to
+/// compute the checksum of a payload that arrives in pieces.
+pub fn message_checksum(payload: &[u8]) -> [u8; 4] { checksum(sha256::Hash::hash(payload)) }
+
+/// Returns the first four bytes of the SHA256 hash of `first`.
+#[cfg(not(hashes_fuzz))]
+fn checksum(first: sha256::Hash) -> [u8; 4] { second_hash_state(first)[0].to_be_bytes() }
+
+/// Returns the SHA256 state words after hashing `first`, i.e. the second hash of SHA256d.
+#[cfg(not(hashes_fuzz))]
+fn second_hash_state(first: sha256::Hash) -> [u32; 8] {
+    // The second hash always has a 32 byte input so its padding is fixed, skip the engine and run
+    // the compression function on the single padded block directly.
+    let mut block = [0; 64];
+    block[..32].copy_from_slice(first.as_byte_array());
+    block[32] = 0x80;
+    block[62] = 0x01; // 256 bits, big-endian.
+
+    let mut state = sha256::HashEngine::new().state_words();
+    sha256::compress(&mut state, &block);
+    state
+}
+
+/// Returns the first four bytes of the SHA256 hash of `first`.
+#[cfg(hashes_fuzz)]
+fn checksum(first: sha256::Hash) -> [u8; 4] {
+    let hash = first.hash_again().to_byte_array();
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
 /// Engine to compute SHA256d hash function.
 #[derive(Debug, Clone)]
 pub struct HashEngine(sha256::HashEngine);
@@ -26,6 +107,11 @@ fn from_engine(e: HashEngine) -> Hash {
 impl HashEngine {
     /// Constructs a new SHA256d hash engine.
     pub const fn new() -> Self { Self(sha256::HashEngine::new()) }
+
+    /// Finalizes this engine, returning the Bitcoin P2P message header checksum.
+    ///
+    /// Streaming equivalent of [`message_checksum`].
+    pub fn checksum(self) -> [u8; 4] { checksum(sha256::Hash::from_engine(self.0)) }
 }
 
 impl Default for HashEngine {
@@ -42,6 +128,37 @@ fn n_bytes_hashed(&self) -> u64 { self.0.n_bytes_hashed() }
     fn finalize(self) -> Self::Hash { Hash::from_engine(self) }
 }
 
+/// Engine to compute legacy (pre-segwit) Bitcoin signature hashes.
+///
+/// Computes `sha256d(preimage || sighash_type)` where `sighash_type` is appended as a 4-byte
+/// little-endian integer. The caller is responsible for serializing the (modified) transaction
+/// into the preimage, this engine only takes care of the trailer and the double hashing.
+#[derive(Debug, Clone, Default)]
+pub struct SigHashEngine(HashEngine);
+
+impl SigHashEngine {
+    /// Constructs a new legacy signature hash engine.
+    pub const fn new() -> Self { Self(HashEngine::new()) }
+
+    /// Adds part of the serialized preimage to the engine.
+    pub fn input(&mut self, data: &[u8]) { self.0.input(data) }
+
+    /// Appends `sighash_type` and returns the signature hash.
+    pub fn finalize(mut self, sighash_type: u32) -> Hash {
+        self.0.input(&sighash_type.to_le_bytes());
+        Hash::from_engine(self.0)
+    }
+}
+
+crate::internal_macros::impl_write!(
+    SigHashEngine,
+    |us: &mut SigHashEngine, buf| {
+        us.input(buf);
+        Ok(buf.len())
+    },
+    |_us| { Ok(()) }
+);
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)] // whether this is used depends on features
@@ -113,6 +230,69 @@ fn fmt_roundtrips() {
         assert_eq!(rinsed, hash)
     }
 
+    #[test]
+    fn byte_array_reversed() {
+        let hash = sha256d::Hash::hash(b"some arbitrary bytes");
+
+        let mut want = hash.to_byte_array();
+        want.reverse();
+        let reversed = hash.to_byte_array_reversed();
+        assert_eq!(reversed, want);
+        assert_ne!(reversed, hash.to_byte_array());
+
+        assert_eq!(sha256d::Hash::from_byte_array_reversed(reversed), hash);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "hex")]
+    fn byte_array_reversed_matches_display() {
+        use alloc::string::ToString;
+
+        use crate::hex::DisplayHex as _;
+
+        // sha256d is displayed backwards, as are txids.
+        let hash = sha256d::Hash::hash(b"some arbitrary bytes");
+        assert_eq!(hash.to_byte_array_reversed().to_lower_hex_string(), hash.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "hex")]
+    fn from_hex_const() {
+        use core::str::FromStr as _;
+
+        const HEX: &str = "6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000";
+        const GENESIS: sha256d::Hash = sha256d::Hash::from_hex_const(HEX);
+
+        assert_eq!(GENESIS, sha256d::Hash::from_str(HEX).unwrap());
+        assert_eq!(GENESIS.to_byte_array_reversed()[0], 0x6f);
+        let upper = "6FE28C0AB6F1B372C1A6A246AE63F74F931E8365E15A089C68D6190000000000";
+        assert_eq!(sha256d::Hash::from_hex_const(upper), GENESIS);
+    }
+
+    #[test]
+    fn message_checksum() {
+        use crate::HashEngine as _;
+
+        assert_eq!(sha256d::message_checksum(&[]), [0x5d, 0xf6, 0xe0, 0xe2]);
+
+        let data: [u8; 300] = core::array::from_fn(|i| i as u8);
+        for len in [0, 1, 31, 32, 55, 56, 64, 65, 119, 120, 300] {
+            let want = sha256d::Hash::hash(&data[..len]).to_byte_array();
+            assert_eq!(sha256d::message_checksum(&data[..len]), want[..4]);
+
+            let mut engine = sha256d::HashEngine::new();
+            for chunk in data[..len].chunks(7) {
+                engine.input(chunk);
+            }
+            assert_eq!(engine.checksum(), want[..4]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_hex_const_invalid() { let _ = sha256d::Hash::from_hex_const("6fe28c0a"); }
+
     #[test]
     #[cfg(feature = "serde")]
     fn sha256_serde() {
@@ -133,6 +313,53 @@ fn sha256_serde() {
             &[Token::Str("6cfb35868c4465b7c289d7d5641563aa973db6a929655282a7bf95c8257f53ef")],
         );
     }
+
+    // From Bitcoin Core's `sighash.json`, the preimage is the transaction with the script code
+    // already substituted in.
+    #[test]
+    #[cfg(feature = "hex")]
+    fn legacy_sighash() {
+        use hex::FromHex;
+
+        let preimage = <[u8; 70]>::from_hex(
+            "b7877f82019c832707a60cf14fba44cfa254d787501fdd676bd58c744f6e951dbba0b3b77f02000000026563\
+             a5a36e500148f89c0500000000085265ac6a6a65acab00000000",
+        )
+        .unwrap();
+        let want = sha256d::Hash::from_hex_const(
+            "cb6e4322955af12eb29613c70e1a00ddbb559c887ba844df0bcdebed736dffbd",
+        );
+
+        let mut engine = sha256d::SigHashEngine::new();
+        for chunk in preimage.chunks(13) {
+            engine.input(chunk);
+        }
+        assert_eq!(engine.finalize(0x9599_6841), want);
+    }
+
+    #[test]
+    fn streaming_engine() {
+        use crate::{sha256, HashEngine as _};
+
+        let data: [u8; 300] = core::array::from_fn(|i| i as u8);
+        for len in [0, 1, 55, 56, 64, 119, 300] {
+            let want = sha256d::Hash::hash(&data[..len]);
+            let single = sha256::Hash::hash(&data[..len]);
+            assert_eq!(want.to_byte_array(), single.hash_again().to_byte_array());
+            assert_eq!(
+                want.to_byte_array(),
+                sha256::Hash::hash(single.as_byte_array()).to_byte_array()
+            );
+
+            for step in [1, 7, 64, 100] {
+                let mut engine = sha256d::HashEngine::new();
+                for chunk in data[..len].chunks(step) {
+                    engine.input(chunk);
+                }
+                assert_eq!(sha256d::Hash::from_engine(engine), want);
+            }
+        }
+    }
 }
 
 #[cfg(bench)]
@@ -161,6 +388,13 @@ pub fn sha256d_1k(bh: &mut Bencher) {
         bh.bytes = bytes.len() as u64;
     }
 
+    #[bench]
+    pub fn message_checksum_80(bh: &mut Bencher) {
+        let bytes = [1u8; 80];
+        bh.iter(|| sha256d::message_checksum(&bytes));
+        bh.bytes = bytes.len() as u64;
+    }
+
     #[bench]
     pub fn sha256d_64k(bh: &mut Bencher) {
         let mut engine = sha256d::Hash::engine();
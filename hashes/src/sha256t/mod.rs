@@ -37,6 +37,85 @@ pub fn hash_byte_chunks<B, I, T>(byte_slices: I) -> Hash<T>
     engine.finalize()
 }
 
+/// Constructs a new SHA256 engine with the literal `prefix` bytes already input.
+///
+/// This is for protocols that domain separate by simply prepending some bytes to the data, i.e.,
+/// `sha256(prefix || data)`. It is **not** the BIP-340 tagged hash scheme used by the rest of this
+/// module, which computes `sha256(sha256(tag) || sha256(tag) || data)`. Mixing the two up silently
+/// produces different hashes so make sure to pick the one your protocol specifies.
+///
+/// The prefix is hashed at runtime each time this function is called. For BIP-340 tagged hashes
+/// use [`Hash::engine`] instead which starts from a precomputed [`Tag::MIDSTATE`].
+///
+/// ```
+/// use bitcoin_hashes::{sha256, sha256t, sha256t_tag, HashEngine as _};
+///
+/// sha256t_tag! {
+///     pub struct FooTag = hash_str("foo");
+/// }
+///
+/// let mut engine = sha256t::raw_prefix_engine(b"foo");
+/// engine.input(b"data");
+/// let raw = sha256::Hash::from_engine(engine);
+/// assert_eq!(raw, sha256::Hash::hash(b"foodata"));
+///
+/// // The BIP-340 scheme produces a different hash for the same tag and data.
+/// let tagged = sha256t::Hash::<FooTag>::hash(b"data");
+/// assert_ne!(raw.to_byte_array(), tagged.to_byte_array());
+/// ```
+pub fn raw_prefix_engine(prefix: &[u8]) -> sha256::HashEngine {
+    let mut engine = sha256::HashEngine::new();
+    engine.input(prefix);
+    engine
+}
+
+/// Computes the BIP-341 `TapTweak` tagged hash of `internal_key || merkle_root`.
+///
+/// Pass `None` as the `merkle_root` for outputs without a script tree.
+pub fn tap_tweak(internal_key: &[u8; 32], merkle_root: Option<&[u8; 32]>) -> sha256::Hash {
+    const MIDSTATE: sha256::Midstate = sha256::Midstate::hash_tag(b"TapTweak");
+
+    let mut engine = sha256::HashEngine::from_midstate(MIDSTATE);
+    engine.input(internal_key);
+    if let Some(merkle_root) = merkle_root {
+        engine.input(merkle_root);
+    }
+    sha256::Hash::from_engine(engine)
+}
+
+/// Computes the BIP-341 `TapBranch` tagged hash of two child node hashes.
+///
+/// The children are sorted lexicographically before hashing, as required by BIP-341, so the
+/// argument order does not matter.
+pub fn tap_branch(a: &[u8; 32], b: &[u8; 32]) -> sha256::Hash {
+    const MIDSTATE: sha256::Midstate = sha256::Midstate::hash_tag(b"TapBranch");
+
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut engine = sha256::HashEngine::from_midstate(MIDSTATE);
+    engine.input(first);
+    engine.input(second);
+    sha256::Hash::from_engine(engine)
+}
+
+/// Computes a length-prefixed commitment to `msg` in the domain `domain_tag`.
+///
+/// The preimage is the BIP-340 tagged hash construction with `msg` prefixed by its length:
+///
+/// ```text
+/// sha256(sha256(domain_tag) || sha256(domain_tag) || CompactSize(msg.len()) || msg)
+/// ```
+///
+/// where `CompactSize` is Bitcoin's variable length integer encoding. The length prefix means a
+/// commitment can't be reinterpreted as one to a message with different boundaries.
+///
+/// The tag midstate is computed at runtime, if the domain is fixed prefer a [`Tag`] type.
+pub fn commit(domain_tag: &[u8], msg: &[u8]) -> sha256::Hash {
+    let mut engine = sha256::HashEngine::from_midstate(sha256::Midstate::hash_tag_fast(domain_tag));
+    crate::hashable::input_compact_size(&mut engine, msg.len() as u64);
+    engine.input(msg);
+    sha256::Hash::from_engine(engine)
+}
+
 /// Trait representing a tag that can be used as a context for SHA256t hashes.
 pub trait Tag {
     /// The [`Midstate`] after pre-tagging the hash engine.
@@ -51,6 +130,12 @@ impl<T> Hash<T>
 where
     T: Tag,
 {
+    /// Length of the hash, in bytes.
+    ///
+    /// The same for all tags, provided so that generic code can use it in `const` context without
+    /// importing the [`crate::Hash`] trait.
+    pub const LEN: usize = 32;
+
     const fn internal_new(arr: [u8; 32]) -> Self { Hash(arr, PhantomData) }
 
     /// Constructs a new hash from the underlying byte array.
@@ -90,16 +175,33 @@ pub fn from_engine(e: HashEngine<T>) -> Hash<T> {
         Hash::from_byte_array(sha256::Hash::from_engine(e.0).to_byte_array())
     }
 
+    /// Produces a hash from the current state of a given engine, leaving it usable.
+    pub fn from_engine_ref(e: &HashEngine<T>) -> Hash<T> { Self::from_engine(e.clone()) }
+
+    /// Returns the midstate the tag `T` seeds the engine with.
+    ///
+    /// Useful in tests to check a tag type is wired up to the expected tag, e.g. by comparing
+    /// against [`sha256::Midstate::hash_tag`].
+    pub const fn tag_midstate() -> sha256::Midstate { T::MIDSTATE }
+
     /// Constructs a new engine.
     pub fn engine() -> HashEngine<T> { HashEngine::default() }
 
+    /// Hashes the bytes of this hash with the same tag, i.e. runs one more round of the hash.
+    ///
+    /// This is **not** a substitute for a key derivation function.
+    #[must_use]
+    pub fn rehash(&self) -> Self { Self::hash(&self.0) }
+
     /// Hashes some bytes.
+    ///
+    /// Accepts anything that can be viewed as bytes, e.g. `Vec<u8>`, `Cow<[u8]>` or `Arc<[u8]>`.
     #[allow(clippy::self_named_constructors)] // Hash is a noun and a verb.
-    pub fn hash(data: &[u8]) -> Self {
+    pub fn hash<D: AsRef<[u8]> + ?Sized>(data: &D) -> Self {
         use crate::HashEngine;
 
         let mut engine = Self::engine();
-        engine.input(data);
+        engine.input(data.as_ref());
         Self::from_engine(engine)
     }
 
@@ -121,6 +223,34 @@ pub fn hash_byte_chunks<B, I>(byte_slices: I) -> Self
 
     /// Returns a reference to the underlying byte array.
     pub const fn as_byte_array(&self) -> &[u8; 32] { &self.0 }
+
+    /// Constructs a new hash from a byte array that is in reverse order.
+    ///
+    /// This is the inverse of [`Self::to_byte_array_reversed`].
+    pub const fn from_byte_array_reversed(bytes: [u8; 32]) -> Self {
+        Self::internal_new(crate::reverse_array(bytes))
+    }
+
+    /// Returns the underlying byte array in reverse order.
+    ///
+    /// Useful for protocols that serialize hashes backwards. Note this is independent of the
+    /// display direction of the hash type.
+    pub const fn to_byte_array_reversed(self) -> [u8; 32] { crate::reverse_array(self.0) }
+
+    /// Parses a hash from a hex string in `const` context.
+    ///
+    /// # Panics
+    ///
+    /// If `s` is not a valid hex string of the correct length. When used to initialize a `const`
+    /// this is a compile time error.
+    pub const fn from_hex_const(s: &str) -> Self {
+        Self::internal_new(crate::hex_to_array(s, false))
+    }
+
+    /// Compares two hashes for equality in `const` context.
+    ///
+    /// This is **not** constant time, use [`crate::cmp::fixed_time_eq`] if that is required.
+    pub const fn const_eq(&self, other: &Self) -> bool { crate::cmp::const_eq(&self.0, &other.0) }
 }
 
 impl<T: Tag> Copy for Hash<T> {}
@@ -146,6 +276,24 @@ fn hash<H: core::hash::Hasher>(&self, h: &mut H) { self.0.hash(h) }
 crate::internal_macros::hash_trait_impls!(256, false, T: Tag);
 
 /// Engine to compute SHA256t hash function.
+///
+/// The engine starts from the tag's precomputed [`Tag::MIDSTATE`] so the tag prefix is never
+/// re-hashed, data can then be input in as many chunks as needed.
+///
+/// ```
+/// use bitcoin_hashes::{sha256t, sha256t_tag, HashEngine as _};
+///
+/// sha256t_tag! {
+///     pub struct FooTag = hash_str("foo");
+/// }
+///
+/// let mut engine = sha256t::Hash::<FooTag>::engine();
+/// engine.input(b"some ");
+/// engine.input(b"data");
+/// let hash = sha256t::Hash::<FooTag>::from_engine(engine);
+///
+/// assert_eq!(hash, sha256t::Hash::<FooTag>::hash(b"some data"));
+/// ```
 #[derive(Debug)]
 pub struct HashEngine<T>(sha256::HashEngine, PhantomData<T>);
 
@@ -180,6 +328,8 @@ fn finalize(self) -> Self::Hash { Hash::from_engine(self) }
     T: crate::sha256t::Tag
 );
 
+crate::internal_macros::impl_extend!(HashEngine<T>, T: crate::sha256t::Tag);
+
 // Workaround macros being unavailable in attributes.
 #[doc(hidden)]
 #[macro_export]
@@ -210,7 +360,7 @@ macro_rules! sha256t_tag_constructor {
 
 #[cfg(test)]
 mod tests {
-    use crate::{sha256, sha256t};
+    use crate::{sha256, sha256t, HashEngine as _};
 
     const TEST_MIDSTATE: [u8; 32] = [
         156, 224, 228, 230, 124, 17, 108, 57, 56, 179, 202, 242, 195, 15, 80, 137, 211, 243, 147,
@@ -249,6 +399,60 @@ fn manually_created_sha256t_hash_type() {
         assert_eq!(TestHash::hash(&[0]).to_string(), HASH_ZERO_FORWARD);
     }
 
+    #[test]
+    fn engine_streaming_matches_one_shot() {
+        use crate::HashEngine as _;
+
+        let data = [0xab_u8; 200];
+        let want = sha256t::Hash::<TestHashTag>::hash(&data);
+
+        for chunk_size in [1, 7, 63, 64, 65, 200] {
+            let mut engine = sha256t::Hash::<TestHashTag>::engine();
+            for chunk in data.chunks(chunk_size) {
+                engine.input(chunk);
+            }
+            assert_eq!(engine.n_bytes_hashed(), 64 + data.len() as u64);
+            assert_eq!(sha256t::Hash::from_engine(engine), want);
+        }
+    }
+
+    #[test]
+    fn tag_midstate() {
+        sha256t_tag! {
+            struct TapLeafTag = hash_str("TapLeaf");
+        }
+
+        let midstate = sha256t::Hash::<TapLeafTag>::tag_midstate();
+        assert_eq!(midstate, sha256::Midstate::hash_tag(b"TapLeaf"));
+        assert_ne!(midstate, sha256::Midstate::hash_tag(b"TapBranch"));
+        assert_eq!(sha256t::Hash::<TestHashTag>::tag_midstate().as_parts().0, &TEST_MIDSTATE);
+    }
+
+    #[test]
+    fn const_surface() {
+        const fn midstate_and_len<T: sha256t::Tag>() -> (sha256::Midstate, usize) {
+            (T::MIDSTATE, sha256t::Hash::<T>::LEN)
+        }
+        const PARTS: (sha256::Midstate, usize) = midstate_and_len::<TestHashTag>();
+
+        assert_eq!(PARTS.0.as_parts(), (&TEST_MIDSTATE, 64));
+        assert_eq!(PARTS.1, 32);
+        assert_eq!(
+            sha256t::Hash::<TestHashTag>::LEN,
+            sha256t::Hash::<TestHashTag>::hash(&[]).as_byte_array().len()
+        );
+    }
+
+    #[test]
+    fn rehash() {
+        let hash = sha256t::Hash::<TestHashTag>::hash(&[0]);
+        assert_eq!(hash.rehash(), sha256t::Hash::<TestHashTag>::hash(hash.as_byte_array()));
+        assert_ne!(
+            hash.rehash().to_byte_array(),
+            sha256::Hash::hash(hash.as_byte_array()).to_byte_array()
+        );
+    }
+
     // We also provide macros to create the tag and the hash type.
     sha256t_tag! {
         /// Test detailed explanation.
@@ -304,4 +508,78 @@ fn macro_created_sha256t_hash_type_prints_forward() {
         // We can also just use the `sha256t::Hash` type directly.
         assert_eq!(sha256t::Hash::<NewTypeTagForward>::hash(&[0]).to_string(), HASH_ZERO_FORWARD);
     }
+
+    // Test vectors from BIP-341 `wallet-test-vectors.json`.
+    #[test]
+    #[cfg(feature = "hex")]
+    fn bip341_tap_tweak_and_branch() {
+        use hex::FromHex;
+
+        let key = |s| <[u8; 32]>::from_hex(s).unwrap();
+
+        let internal_key = key("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d");
+        assert_eq!(
+            sha256t::tap_tweak(&internal_key, None),
+            sha256::Hash::from_hex_const(
+                "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70"
+            ),
+        );
+
+        let internal_key = key("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27");
+        let merkle_root = key("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21");
+        assert_eq!(
+            sha256t::tap_tweak(&internal_key, Some(&merkle_root)),
+            sha256::Hash::from_hex_const(
+                "cbd8679ba636c1110ea247542cfbd964131a6be84f873f7f3b62a777528ed001"
+            ),
+        );
+
+        let internal_key = key("ee4fe085983462a184015d1f782d6a5f8b9c2b60130aff050ce221ecf3786592");
+        let leaf_a = key("8ad69ec7cf41c2a4001fd1f738bf1e505ce2277acdcaa63fe4765192497f47a7");
+        let leaf_b = key("f224a923cd0021ab202ab139cc56802ddb92dcfc172b9212261a539df79a112a");
+        let merkle_root = sha256t::tap_branch(&leaf_a, &leaf_b);
+        assert_eq!(sha256t::tap_branch(&leaf_b, &leaf_a), merkle_root);
+        assert_eq!(
+            merkle_root,
+            sha256::Hash::from_hex_const(
+                "6c2dc106ab816b73f9d07e3cd1ef2c8c1256f519748e0813e4edd2405d277bef"
+            ),
+        );
+        assert_eq!(
+            sha256t::tap_tweak(&internal_key, Some(merkle_root.as_byte_array())),
+            sha256::Hash::from_hex_const(
+                "9e0517edc8259bb3359255400b23ca9507f2a91cd1e4250ba068b4eafceba4a9"
+            ),
+        );
+    }
+
+    // Hand-computed with Python's `hashlib` following the preimage layout documented on `commit`.
+    #[test]
+    fn commit() {
+        let tag = b"example/commit";
+        assert_eq!(
+            sha256t::commit(tag, b"hello"),
+            sha256::Hash::from_hex_const(
+                "b0c01cb600c82c4da345e3cb2a76f6a13144f88d55fdd9ff3d284c14355084cd"
+            ),
+        );
+        assert_eq!(
+            sha256t::commit(tag, &[]),
+            sha256::Hash::from_hex_const(
+                "4fbcc224ae25ac807bb97bde9fdb5e5e06256beb05716b55938cee03bb734871"
+            ),
+        );
+        // Three byte `CompactSize` prefix.
+        assert_eq!(
+            sha256t::commit(tag, &[0xab; 300]),
+            sha256::Hash::from_hex_const(
+                "4f1be5190a99d4e7882742153c767a7e435b7c325c1c34539e3d1e4e8d9528b4"
+            ),
+        );
+
+        let mut engine = sha256::HashEngine::from_midstate(sha256::Midstate::hash_tag(tag));
+        engine.input(&[5]);
+        engine.input(b"hello");
+        assert_eq!(sha256t::commit(tag, b"hello"), sha256::Hash::from_engine(engine));
+    }
 }
@@ -10,6 +10,34 @@
     "Output of the SHA384 hash function."
 }
 
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        const EMPTY: [u8; 48] = crate::hex_to_array(
+            concat!(
+                "38b060a751ac96384cd9327eb1b1e36a21fdb71114be0743",
+                "4c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b"
+            ),
+            false,
+        );
+        const ABC: [u8; 48] = crate::hex_to_array(
+            concat!(
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded163",
+                "1a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+            ),
+            false,
+        );
+        const TWO_BLOCK: [u8; 48] = crate::hex_to_array(
+            concat!(
+                "3391fdddfc8dc7393707a65b1b4709397cf8b1d162af05ab",
+                "fe8f450de5f36bc6b0455a8520bc4e6f5fe95b1fe3c8452b"
+            ),
+            false,
+        );
+
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
+    }
+}
+
 fn from_engine(e: HashEngine) -> Hash {
     let mut ret = [0; 48];
     ret.copy_from_slice(&sha512::from_engine(e.0).as_byte_array()[..48]);
@@ -73,6 +73,11 @@ macro_rules! round(
 impl HashEngine {
     // Algorithm copied from libsecp256k1
     pub(crate) fn process_block(&mut self) {
+        crate::internal_macros::trace_span!(
+            "sha512::process_block",
+            bytes_hashed = self.bytes_hashed,
+            backend = "software"
+        );
         debug_assert_eq!(self.buffer.len(), BLOCK_SIZE);
 
         let mut w = [0u64; 16];
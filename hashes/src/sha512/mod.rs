@@ -10,9 +10,7 @@
 #[cfg(bench)]
 mod tests;
 
-use core::cmp;
-
-use crate::{incomplete_block_len, HashEngine as _};
+use core::{cmp, convert, fmt};
 
 crate::internal_macros::general_hash_type! {
     512,
@@ -20,30 +18,47 @@
     "Output of the SHA512 hash function."
 }
 
-#[cfg(not(hashes_fuzz))]
-pub(crate) fn from_engine(mut e: HashEngine) -> Hash {
-    // pad buffer with a single 1-bit then all 0s, until there are exactly 16 bytes remaining
-    let n_bytes_hashed = e.bytes_hashed;
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        const EMPTY: [u8; 64] = crate::hex_to_array(
+            concat!(
+                "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce",
+                "47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+            ),
+            false,
+        );
+        const ABC: [u8; 64] = crate::hex_to_array(
+            concat!(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a",
+                "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            ),
+            false,
+        );
+        const TWO_BLOCK: [u8; 64] = crate::hex_to_array(
+            concat!(
+                "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c335",
+                "96fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445"
+            ),
+            false,
+        );
 
-    let zeroes = [0; BLOCK_SIZE - 16];
-    e.input(&[0x80]);
-    if incomplete_block_len(&e) > zeroes.len() {
-        e.input(&zeroes);
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
     }
-    let pad_length = zeroes.len() - incomplete_block_len(&e);
-    e.input(&zeroes[..pad_length]);
-    debug_assert_eq!(incomplete_block_len(&e), zeroes.len());
+}
 
-    e.input(&[0; 8]);
-    e.input(&(8 * n_bytes_hashed).to_be_bytes());
-    debug_assert_eq!(incomplete_block_len(&e), 0);
+#[cfg(not(hashes_fuzz))]
+pub(crate) fn from_engine(mut e: HashEngine) -> Hash {
+    crate::internal_macros::trace_span!("sha512::from_engine", bytes_hashed = e.bytes_hashed);
 
-    Hash(e.midstate())
+    let length = u128::from(e.bytes_hashed).wrapping_mul(8).to_be_bytes();
+    crate::md_pad(&mut e, &length);
+
+    Hash(e.midstate_unchecked().bytes)
 }
 
 #[cfg(hashes_fuzz)]
 pub(crate) fn from_engine(e: HashEngine) -> Hash {
-    let mut hash = e.midstate();
+    let mut hash = e.midstate_unchecked().bytes;
     hash[0] ^= 0xff; // Make this distinct from SHA-256
     Hash(hash)
 }
@@ -78,20 +93,53 @@ fn default() -> Self { Self::new() }
 }
 
 impl HashEngine {
+    /// Constructs a new [`HashEngine`] from a [`Midstate`].
+    ///
+    /// Please see docs on [`Midstate`] before using this function.
+    pub fn from_midstate(midstate: Midstate) -> HashEngine {
+        let mut ret = [0; 8];
+        for (ret_val, midstate_bytes) in ret.iter_mut().zip(midstate.as_ref().bitcoin_as_chunks().0)
+        {
+            *ret_val = u64::from_be_bytes(*midstate_bytes);
+        }
+
+        HashEngine { buffer: [0; BLOCK_SIZE], h: ret, bytes_hashed: midstate.bytes_hashed }
+    }
+
+    /// Returns `true` if the midstate can be extracted from this engine.
+    ///
+    /// The midstate can only be extracted if the number of bytes input into
+    /// the hash engine is a multiple of 128. See caveat on [`Self::midstate`].
+    ///
+    /// Please see docs on [`Midstate`] before using this function.
+    pub const fn can_extract_midstate(&self) -> bool { self.bytes_hashed % 128 == 0 }
+
+    /// Outputs the midstate of the hash engine.
+    ///
+    /// Please see docs on [`Midstate`] before using this function.
+    pub fn midstate(&self) -> Result<Midstate, MidstateError> {
+        if !self.can_extract_midstate() {
+            return Err(MidstateError { invalid_n_bytes_hashed: self.bytes_hashed });
+        }
+        Ok(self.midstate_unchecked())
+    }
+
+    // Does not check that `HashEngine::can_extract_midstate`.
     #[cfg(not(hashes_fuzz))]
-    pub(crate) fn midstate(&self) -> [u8; 64] {
+    fn midstate_unchecked(&self) -> Midstate {
         let mut ret = [0; 64];
         for (val, ret_bytes) in self.h.iter().zip(ret.bitcoin_as_chunks_mut().0) {
             *ret_bytes = val.to_be_bytes();
         }
-        ret
+        Midstate { bytes: ret, bytes_hashed: self.bytes_hashed }
     }
 
+    // Does not check that `HashEngine::can_extract_midstate`.
     #[cfg(hashes_fuzz)]
-    pub(crate) fn midstate(&self) -> [u8; 64] {
+    fn midstate_unchecked(&self) -> Midstate {
         let mut ret = [0; 64];
         ret.copy_from_slice(&self.buffer[..64]);
-        ret
+        Midstate { bytes: ret, bytes_hashed: self.bytes_hashed }
     }
 
     /// Constructs a new hash engine suitable for use constructing a `sha512_256::HashEngine`.
@@ -130,3 +178,92 @@ fn n_bytes_hashed(&self) -> u64 { self.bytes_hashed }
     crate::internal_macros::engine_input_impl!();
     fn finalize(self) -> Self::Hash { Hash::from_engine(self) }
 }
+
+/// Unfinalized output of the SHA512 hash function.
+///
+/// The `Midstate` type is obscure and specialized and should not be used unless you are sure of
+/// what you are doing.
+///
+/// It represents "partially hashed data" but does not itself have properties of cryptographic
+/// hashes. For example, when (ab)used as hashes, midstates are vulnerable to trivial
+/// length-extension attacks. They are typically used to optimize the computation of full hashes,
+/// e.g. by hashing a fixed 128-byte prefix once and resuming from the stored midstate.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Midstate {
+    /// Raw bytes of the midstate i.e., the already-hashed contents of the hash engine.
+    bytes: [u8; 64],
+    /// Number of bytes hashed to achieve this midstate.
+    // INVARIANT must always be a multiple of 128.
+    bytes_hashed: u64,
+}
+
+impl Midstate {
+    /// Construct a new [`Midstate`] from the `state` and the `bytes_hashed` to get to that state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_hashed` is not a multiple of 128.
+    pub const fn new(state: [u8; 64], bytes_hashed: u64) -> Self {
+        if bytes_hashed % 128 != 0 {
+            panic!("bytes hashed is not a multiple of 128");
+        }
+
+        Midstate { bytes: state, bytes_hashed }
+    }
+
+    /// Deconstructs the [`Midstate`], returning the underlying byte array and number of bytes hashed.
+    pub const fn as_parts(&self) -> (&[u8; 64], u64) { (&self.bytes, self.bytes_hashed) }
+
+    /// Deconstructs the [`Midstate`], returning the underlying byte array and number of bytes hashed.
+    pub const fn to_parts(self) -> ([u8; 64], u64) { (self.bytes, self.bytes_hashed) }
+}
+
+impl Default for Midstate {
+    fn default() -> Self { Midstate { bytes: [0; 64], bytes_hashed: 0 } }
+}
+
+impl fmt::Debug for Midstate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct Encoder<'a> {
+            bytes: &'a [u8; 64],
+        }
+        impl fmt::Debug for Encoder<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { crate::debug_hex(self.bytes, f) }
+        }
+
+        f.debug_struct("Midstate")
+            .field("bytes", &Encoder { bytes: &self.bytes })
+            .field("length", &self.bytes_hashed)
+            .finish()
+    }
+}
+
+impl convert::AsRef<[u8]> for Midstate {
+    fn as_ref(&self) -> &[u8] { &self.bytes }
+}
+
+/// `Midstate` invariant violated (not a multiple of 128).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MidstateError {
+    /// The invalid number of bytes hashed.
+    invalid_n_bytes_hashed: u64,
+}
+
+impl fmt::Display for MidstateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid number of bytes hashed {} (should have been a multiple of 128)",
+            self.invalid_n_bytes_hashed
+        )
+    }
+}
+
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for MidstateError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for MidstateError {}
+    }
+}
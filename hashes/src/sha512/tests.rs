@@ -107,3 +107,28 @@ fn sha512_serde() {
         )],
     );
 }
+
+#[test]
+fn midstate_resume() {
+    use crate::{sha512, HashEngine};
+
+    let data = [0xab_u8; 300];
+
+    let mut engine = sha512::HashEngine::new();
+    engine.input(&data[..127]);
+    assert!(engine.midstate().is_err());
+    engine.input(&data[127..128]);
+    let midstate = engine.midstate().expect("128 bytes hashed");
+    assert_eq!(midstate.as_parts().1, 128);
+
+    let mut resumed = sha512::HashEngine::from_midstate(midstate);
+    resumed.input(&data[128..]);
+    assert_eq!(sha512::Hash::from_engine(resumed), sha512::Hash::hash(&data));
+
+    let (bytes, len) = midstate.to_parts();
+    assert_eq!(sha512::Midstate::new(bytes, len), midstate);
+}
+
+#[test]
+#[should_panic]
+fn midstate_new_invalid_length() { let _ = crate::sha512::Midstate::new([0; 64], 64); }
@@ -15,6 +15,25 @@
     "Output of the SHA512/256 hash function.\n\nSHA512/256 is a hash function that uses the sha512 algorithm but it truncates the output to 256 bits. It has different initial constants than sha512 so it produces an entirely different hash compared to sha512. More information at <https://eprint.iacr.org/2010/548.pdf>."
 }
 
+impl crate::TestVectors for HashEngine {
+    fn test_vectors() -> &'static [(&'static [u8], &'static [u8])] {
+        const EMPTY: [u8; 32] = crate::hex_to_array(
+            "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a",
+            false,
+        );
+        const ABC: [u8; 32] = crate::hex_to_array(
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+            false,
+        );
+        const TWO_BLOCK: [u8; 32] = crate::hex_to_array(
+            "bde8e1f9f19bb9fd3406c90ec6bc47bd36d8ada9f11880dbc8a22a7078b6a461",
+            false,
+        );
+
+        &[(b"", &EMPTY), (b"abc", &ABC), (crate::TWO_BLOCK_MESSAGE, &TWO_BLOCK)]
+    }
+}
+
 fn from_engine(e: HashEngine) -> Hash {
     let mut ret = [0; 32];
     ret.copy_from_slice(&sha512::from_engine(e.0).as_byte_array()[..32]);
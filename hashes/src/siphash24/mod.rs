@@ -178,6 +178,9 @@ pub fn engine(k0: u64, k1: u64) -> HashEngine { HashEngine::with_keys(k0, k1) }
     /// Produces a hash from the current state of a given engine.
     pub fn from_engine(e: HashEngine) -> Hash { from_engine(e) }
 
+    /// Produces a hash from the current state of a given engine, leaving it usable.
+    pub fn from_engine_ref(e: &HashEngine) -> Hash { from_engine(e.clone()) }
+
     /// Hashes the given data with an engine with the provided keys.
     pub fn hash_with_keys(k0: u64, k1: u64, data: &[u8]) -> Hash {
         let mut engine = HashEngine::with_keys(k0, k1);
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Expansion of a message into uniform bytes using a Merkle-Damgård hash function.
+//!
+//! Implementation of `expand_message_xmd` from [RFC 9380] section 5.3.1, as used by hash-to-curve
+//! (e.g. with SHA256 for the `*_XMD:SHA-256_*` suites).
+//!
+//! [RFC 9380]: <https://www.rfc-editor.org/rfc/rfc9380.html>
+
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::{cmp, fmt};
+
+use crate::{HashEngine, IsByteArray};
+
+/// Maximum length of a domain separation tag.
+const MAX_DST_LEN: usize = 255;
+
+/// Maximum number of hash outputs in the expanded message.
+const MAX_OUTPUT_BLOCKS: usize = 255;
+
+/// Maximum length of the expanded message, as it is encoded in two bytes.
+const MAX_OUTPUT_LEN: usize = 65535;
+
+/// Invalid input to [`expand_message_xmd`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum XmdError {
+    /// The domain separation tag is empty or longer than 255 bytes.
+    ///
+    /// Longer tags have to be hashed first, as described in RFC 9380 section 5.3.3.
+    InvalidDstLength(usize),
+    /// The requested output is longer than allowed for the hash function.
+    OutputTooLong {
+        /// The requested length.
+        len: usize,
+        /// The maximum length, the smaller of 255 hash outputs and 65535 bytes.
+        max: usize,
+    },
+}
+
+impl fmt::Display for XmdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XmdError::InvalidDstLength(len) =>
+                write!(f, "invalid domain separation tag length {} (must be 1 to 255)", len),
+            XmdError::OutputTooLong { len, max } =>
+                write!(f, "output length {} exceeds the maximum of {} bytes", len, max),
+        }
+    }
+}
+
+internals::rust_version::rust_version! {
+    if >= 1.81 {
+        impl core::error::Error for XmdError {}
+    } else {
+        #[cfg(feature = "std")]
+        impl std::error::Error for XmdError {}
+    }
+}
+
+/// Expands `msg` into `len_in_bytes` uniform bytes, domain separated by `dst`.
+///
+/// # Errors
+///
+/// If `dst` is empty or longer than 255 bytes or if `len_in_bytes` is longer than 255 outputs of
+/// `T` or 65535 bytes.
+#[cfg(feature = "alloc")]
+pub fn expand_message_xmd<T: HashEngine + Default>(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<u8>, XmdError> {
+    let mut out = vec![0; len_in_bytes];
+    expand_message_xmd_into::<T>(msg, dst, &mut out)?;
+    Ok(out)
+}
+
+/// Expands `msg` into uniform bytes filling `out`, domain separated by `dst`.
+///
+/// # Errors
+///
+/// If `dst` is empty or longer than 255 bytes or if `out` is longer than 255 outputs of `T` or
+/// 65535 bytes.
+pub fn expand_message_xmd_into<T: HashEngine + Default>(
+    msg: &[u8],
+    dst: &[u8],
+    out: &mut [u8],
+) -> Result<(), XmdError> {
+    let hash_len = T::Bytes::LEN;
+    if dst.is_empty() || dst.len() > MAX_DST_LEN {
+        return Err(XmdError::InvalidDstLength(dst.len()));
+    }
+    let max = cmp::min(MAX_OUTPUT_BLOCKS * hash_len, MAX_OUTPUT_LEN);
+    if out.len() > max {
+        return Err(XmdError::OutputTooLong { len: out.len(), max });
+    }
+    // DST_prime = DST || I2OSP(len(DST), 1)
+    let input_dst_prime = |engine: &mut T| {
+        engine.input(dst);
+        engine.input(&[dst.len() as u8]);
+    };
+
+    // b_0 = H(Z_pad || msg || I2OSP(len_in_bytes, 2) || I2OSP(0, 1) || DST_prime)
+    let mut engine = T::default();
+    let zeros = [0; 64];
+    let mut pad_len = T::BLOCK_SIZE;
+    while pad_len > 0 {
+        let len = cmp::min(pad_len, zeros.len());
+        engine.input(&zeros[..len]);
+        pad_len -= len;
+    }
+    engine.input(msg);
+    engine.input(&(out.len() as u16).to_be_bytes());
+    engine.input(&[0]);
+    input_dst_prime(&mut engine);
+    let b_0 = engine.finalize();
+
+    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
+    // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime)
+    for i in 0..(out.len() + hash_len - 1) / hash_len {
+        let mut engine = T::default();
+        if i == 0 {
+            engine.input(b_0.as_ref());
+        } else {
+            let prev = &out[(i - 1) * hash_len..i * hash_len];
+            let mut xored = [0; 64];
+            for (b_0, prev) in b_0.as_ref().chunks(xored.len()).zip(prev.chunks(xored.len())) {
+                for (x, (a, b)) in xored.iter_mut().zip(b_0.iter().zip(prev)) {
+                    *x = a ^ b;
+                }
+                engine.input(&xored[..b_0.len()]);
+            }
+        }
+        engine.input(&[i as u8 + 1]);
+        input_dst_prime(&mut engine);
+        let b_i = engine.finalize();
+
+        let start = i * hash_len;
+        let end = cmp::min(start + hash_len, out.len());
+        out[start..end].copy_from_slice(&b_i.as_ref()[..end - start]);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+#[cfg(feature = "hex")]
+mod tests {
+    use hex::prelude::{DisplayHex, FromHex};
+
+    use super::*;
+    use crate::{sha256, sha512};
+
+    const DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA256-128";
+
+    #[test]
+    fn rfc9380_sha256() {
+        let long_128: Vec<u8> = [&b"q128_"[..], &[b'q'; 128]].concat();
+        let long_512: Vec<u8> = [&b"a512_"[..], &[b'a'; 512]].concat();
+        #[rustfmt::skip]
+        let vectors: [(&[u8], &str); 6] = [
+            (b"", "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"),
+            (b"abc", "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"),
+            (b"abcdef0123456789", "eff31487c770a893cfb36f912fbfcbff40d5661771ca4b2cb4eafe524333f5c1"),
+            (&long_128, "b23a1d2b4d97b2ef7785562a7e8bac7eed54ed6e97e29aa51bfe3f12ddad1ff9"),
+            (b"", "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbee0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dcc541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"),
+            (&long_512, "546aff5444b5b79aa6148bd81728704c32decb73a3ba76e9e75885cad9def1d06d6792f8a7d12794e90efed817d96920d728896a4510864370c207f99bd4a608ea121700ef01ed879745ee3e4ceef777eda6d9e5e38b90c86ea6fb0b36504ba4a45d22e86f6db5dd43d98a294bebb9125d5b794e9d2a81181066eb954966a487"),
+        ];
+
+        for (msg, want) in vectors {
+            let want = Vec::from_hex(want).unwrap();
+            let got = expand_message_xmd::<sha256::HashEngine>(msg, DST, want.len()).unwrap();
+            assert_eq!(got.to_lower_hex_string(), want.to_lower_hex_string());
+        }
+    }
+
+    #[test]
+    fn output_depends_on_length() {
+        // The length is part of the input, a shorter output is not a prefix of a longer one.
+        let short = expand_message_xmd::<sha256::HashEngine>(b"abc", DST, 40).unwrap();
+        let long = expand_message_xmd::<sha256::HashEngine>(b"abc", DST, 80).unwrap();
+        assert_ne!(short[..], long[..40]);
+
+        let mut out = [0; 40];
+        expand_message_xmd_into::<sha256::HashEngine>(b"abc", DST, &mut out).unwrap();
+        assert_eq!(out[..], short[..]);
+    }
+
+    #[test]
+    fn limits() {
+        let expand = expand_message_xmd::<sha256::HashEngine>;
+
+        assert_eq!(expand(b"abc", b"", 32), Err(XmdError::InvalidDstLength(0)));
+        assert_eq!(expand(b"abc", &[b'x'; 256], 32), Err(XmdError::InvalidDstLength(256)));
+        assert!(expand(b"abc", &[b'x'; 255], 32).is_ok());
+
+        assert_eq!(
+            expand(b"abc", DST, 8161),
+            Err(XmdError::OutputTooLong { len: 8161, max: 8160 })
+        );
+        assert_eq!(expand(b"abc", DST, 8160).unwrap().len(), 8160);
+
+        let expand = expand_message_xmd::<sha512::HashEngine>;
+        assert_eq!(expand(b"abc", DST, 16320).unwrap().len(), 16320);
+        assert_eq!(
+            expand(b"abc", DST, 16321),
+            Err(XmdError::OutputTooLong { len: 16321, max: 16320 })
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Tests for `#[derive(Hashable)]`.
+
+#![cfg(feature = "derive")]
+
+use bitcoin_hashes::{sha256, HashEngine as _, Hashable};
+
+fn engine_of<T: Hashable>(value: &T) -> sha256::HashEngine {
+    let mut engine = sha256::HashEngine::new();
+    value.hash_into(&mut engine);
+    engine
+}
+
+fn engine_from(bytes: &[u8]) -> sha256::HashEngine {
+    let mut engine = sha256::HashEngine::new();
+    engine.input(bytes);
+    engine
+}
+
+#[derive(Hashable)]
+struct Named {
+    a: u16,
+    #[hashable(big_endian)]
+    b: u32,
+    #[hashable(len_prefix)]
+    c: Vec<u8>,
+    #[hashable(skip)]
+    #[allow(dead_code)]
+    d: String,
+    e: sha256::Hash,
+}
+
+#[test]
+fn named_struct() {
+    let e = sha256::Hash::hash(b"e");
+    let value = Named { a: 0x0102, b: 0x0304_0506, c: vec![0xaa, 0xbb], d: "skipped".into(), e };
+
+    let mut want = vec![0x02, 0x01, 0x03, 0x04, 0x05, 0x06, 0x02, 0xaa, 0xbb];
+    want.extend_from_slice(e.as_byte_array());
+    assert_eq!(engine_of(&value), engine_from(&want));
+}
+
+#[derive(Hashable)]
+#[hashable(big_endian)]
+struct Tuple(u16, #[hashable(little_endian)] u16, [u32; 2]);
+
+#[test]
+fn tuple_struct_big_endian() {
+    let value = Tuple(0x0102, 0x0102, [0x0304_0506, 0x0708_090a]);
+    let want = [0x01, 0x02, 0x02, 0x01, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a];
+    assert_eq!(engine_of(&value), engine_from(&want));
+}
+
+#[derive(Hashable)]
+struct Generic<T> {
+    #[hashable(len_prefix)]
+    items: Vec<T>,
+    nested: Tuple,
+}
+
+#[test]
+fn generic_and_nested() {
+    let value = Generic { items: vec![1_u16, 2], nested: Tuple(0, 0, [0, 0]) };
+    let mut want = vec![0x02, 0x01, 0x00, 0x02, 0x00];
+    want.extend_from_slice(&[0; 12]);
+    assert_eq!(engine_of(&value), engine_from(&want));
+}
+
+#[derive(Hashable)]
+struct Unit;
+
+#[test]
+fn unit_struct() {
+    assert_eq!(engine_of(&Unit), sha256::HashEngine::new());
+}
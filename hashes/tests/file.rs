@@ -0,0 +1,59 @@
+//! Tests for hashing files.
+
+#![cfg(feature = "std")]
+
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use bitcoin_hashes::sha256;
+
+/// A file in the temporary directory, removed on drop.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, data: &[u8]) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("bitcoin_hashes_{}_{}", std::process::id(), name));
+        std::fs::File::create(&path).unwrap().write_all(data).unwrap();
+        TempFile(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) { let _ = std::fs::remove_file(&self.0); }
+}
+
+fn data() -> Vec<u8> { (0..100_000_u32).map(|i| (i * 31) as u8).collect() }
+
+#[test]
+fn hash_file() {
+    let data = data();
+    for len in [0, 1, 64, 8193, 100_000] {
+        let file = TempFile::new(&format!("hash_file_{}", len), &data[..len]);
+        let got = sha256::Hash::hash_file(&file.0).unwrap();
+        assert_eq!(got, sha256::Hash::hash(&data[..len]));
+    }
+
+    let missing = std::env::temp_dir()
+        .join(format!("bitcoin_hashes_{}_hash_file_missing", std::process::id()));
+    let err = sha256::Hash::hash_file(&missing).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn hash_file_mmap() {
+    let data = data();
+    for len in [0, 1, 64, 8193, 100_000] {
+        let file = TempFile::new(&format!("hash_file_mmap_{}", len), &data[..len]);
+        // SAFETY: The file is private to this test and not modified while hashing.
+        let got = unsafe { sha256::Hash::hash_file_mmap(&file.0) }.unwrap();
+        assert_eq!(got, sha256::Hash::hash(&data[..len]));
+    }
+
+    let missing = std::env::temp_dir()
+        .join(format!("bitcoin_hashes_{}_hash_file_mmap_missing", std::process::id()));
+    // SAFETY: The file does not exist.
+    let err = unsafe { sha256::Hash::hash_file_mmap(&missing) }.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
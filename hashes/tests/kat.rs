@@ -0,0 +1,77 @@
+//! Known answer tests for each general purpose hash type.
+//!
+//! Runs the test vectors each module provides through `TestVectors` (empty input, "abc" and
+//! the 448-bit two-block message) against every module, plus one million repetitions of 'a' which
+//! is too large to keep in the library. Each vector is also fed through the engine one byte at a
+//! time and in odd sized chunks to exercise the buffering logic.
+
+#![cfg(feature = "hex")]
+
+use core::fmt::Debug;
+use core::str::FromStr;
+
+use bitcoin_hashes::{Hash as _, HashEngine, TestVectors};
+
+fn million_a() -> Vec<u8> { vec![b'a'; 1_000_000] }
+
+fn check<E: HashEngine + Default>(input: &[u8], want: &[u8]) {
+    let mut engine = E::default();
+    engine.input(input);
+    let hash = engine.finalize();
+    assert_eq!(hash.as_byte_array().as_ref(), want);
+
+    let mut engine = E::default();
+    for byte in input.iter().take(1_000) {
+        engine.input(&[*byte]);
+    }
+    for chunk in input.get(1_000..).unwrap_or(&[]).chunks(7) {
+        engine.input(chunk);
+    }
+    assert_eq!(engine.n_bytes_hashed(), input.len() as u64);
+    assert_eq!(engine.finalize().as_byte_array().as_ref(), want);
+}
+
+fn check_test_vectors<E: TestVectors>() {
+    let vectors = E::test_vectors();
+    assert!(vectors.len() >= 3);
+    for (input, want) in vectors {
+        check::<E>(input, want);
+    }
+}
+
+fn check_million_a<E: HashEngine + Default>(want: &str)
+where
+    E::Hash: FromStr,
+    <E::Hash as FromStr>::Err: Debug,
+{
+    let want: E::Hash = want.parse().unwrap();
+    check::<E>(&million_a(), want.as_byte_array().as_ref());
+}
+
+macro_rules! impl_kat {
+    ($($module:ident => $million:literal);* $(;)?) => {
+        $(
+            mod $module {
+                use bitcoin_hashes::$module::HashEngine;
+
+                #[test]
+                fn test_vectors() { super::check_test_vectors::<HashEngine>() }
+
+                #[test]
+                fn million_a() { super::check_million_a::<HashEngine>($million) }
+            }
+        )*
+    }
+}
+
+impl_kat! {
+    sha1 => "34aa973cd4c4daa4f61eeb2bdbad27316534016f";
+    ripemd160 => "52783243c1697bdbe16d37f97f68f08325dc1528";
+    sha256 => "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0";
+    // Displayed backwards.
+    sha256d => "88661512a78701a68778d78b5e70e50748fe1a9f74b206521b3e56779418d180";
+    hash160 => "f9be0e104ef2ed83a7ddb4765780951405e56ba4";
+    sha384 => "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985";
+    sha512 => "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b";
+    sha512_256 => "9a59a052930187a97038cae692f30708aa6491923ef5194394dc68d56c74fb21";
+}
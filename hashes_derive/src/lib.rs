@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Derive macro for the `bitcoin_hashes::Hashable` trait.
+//!
+//! Do not use this crate directly, enable the `derive` feature of `bitcoin_hashes` and use the
+//! re-exported `bitcoin_hashes::Hashable` derive instead. See the documentation there for the list
+//! of supported attributes.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields, Index, LitStr,
+    Path, Result,
+};
+
+/// Derives `Hashable` for a struct by hashing each field in declaration order.
+#[proc_macro_derive(Hashable, attributes(hashable))]
+pub fn derive_hashable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Byte order used for integer fields.
+#[derive(Copy, Clone)]
+enum Endian {
+    Little,
+    Big,
+}
+
+/// Options set by the container attribute.
+struct ContainerOpts {
+    krate: Path,
+    endian: Endian,
+}
+
+/// Options set by a field attribute.
+#[derive(Default)]
+struct FieldOpts {
+    endian: Option<Endian>,
+    len_prefix: bool,
+    skip: bool,
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream2> {
+    let opts = parse_container_attrs(&input.attrs)?;
+    let krate = &opts.krate;
+
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        Data::Enum(ref data) =>
+            return Err(Error::new(
+                data.enum_token.span(),
+                "`Hashable` can only be derived for structs",
+            )),
+        Data::Union(ref data) =>
+            return Err(Error::new(
+                data.union_token.span(),
+                "`Hashable` can only be derived for structs",
+            )),
+    };
+
+    let mut body = TokenStream2::new();
+    let members: Vec<_> = match fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|f| (f, f.ident.as_ref().expect("named field").to_token_stream()))
+            .collect(),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f, Index::from(i).to_token_stream()))
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+    for (field, member) in members {
+        let field_opts = parse_field_attrs(&field.attrs)?;
+        if field_opts.skip {
+            continue;
+        }
+        if field_opts.len_prefix {
+            body.extend(quote! {
+                #krate::hashable::input_compact_size(engine, self.#member.len() as u64);
+            });
+        }
+        let method = match field_opts.endian.unwrap_or(opts.endian) {
+            Endian::Little => quote!(hash_into),
+            Endian::Big => quote!(hash_into_be),
+        };
+        body.extend(quote! {
+            #krate::Hashable::#method(&self.#member, engine);
+        });
+    }
+
+    for param in input.generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#krate::Hashable));
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #krate::Hashable for #name #ty_generics #where_clause {
+            fn hash_into<__E: #krate::HashEngine>(&self, engine: &mut __E) {
+                #body
+            }
+        }
+    })
+}
+
+fn parse_container_attrs(attrs: &[Attribute]) -> Result<ContainerOpts> {
+    let mut opts = ContainerOpts { krate: parse_quote!(::bitcoin_hashes), endian: Endian::Little };
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("hashable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                let path: LitStr = meta.value()?.parse()?;
+                opts.krate = path.parse()?;
+            } else if meta.path.is_ident("little_endian") {
+                opts.endian = Endian::Little;
+            } else if meta.path.is_ident("big_endian") {
+                opts.endian = Endian::Big;
+            } else {
+                return Err(meta.error("unknown `hashable` container attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(opts)
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> Result<FieldOpts> {
+    let mut opts = FieldOpts::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("hashable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("little_endian") {
+                opts.endian = Some(Endian::Little);
+            } else if meta.path.is_ident("big_endian") {
+                opts.endian = Some(Endian::Big);
+            } else if meta.path.is_ident("len_prefix") {
+                opts.len_prefix = true;
+            } else if meta.path.is_ident("skip") {
+                opts.skip = true;
+            } else {
+                return Err(meta.error("unknown `hashable` field attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(opts)
+}
@@ -1,4 +1,4 @@
-const MAX_USED_VERSION: u64 = 80;
+const MAX_USED_VERSION: u64 = 81;
 
 use std::io;
 